@@ -0,0 +1,556 @@
+use thiserror::Error;
+
+use crate::shell::Shell;
+use crate::variables::is_valid_name;
+
+const MAX_RECURSION: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum ArithError {
+    #[error("{0}: syntax error in expression (error token is \"{1}\")")]
+    Syntax(String, String),
+    #[error("{0}: division by 0 (error token is \"{1}\")")]
+    DivisionByZero(String, String),
+    #[error("{0}: attempted assignment to non-variable (error token is \"{1}\")")]
+    NotAVariable(String, String),
+    #[error("{0}: expression recursion level exceeded")]
+    Recursion(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Num(i64),
+    Ident(String),
+    Op(&'static str),
+}
+
+#[derive(Debug)]
+enum Expr {
+    Num(i64),
+    Var(String),
+    Unary(&'static str, Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+    Assign(String, &'static str, Box<Expr>),
+    Increment {
+        name: String,
+        delta: i64,
+        prefix: bool,
+    },
+    Conditional(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+const OPERATORS: &[&str] = &[
+    "<<=", ">>=", "**", "++", "--", "<<", ">>", "<=", ">=", "==", "!=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "&=", "^=", "|=", "+", "-", "*", "/", "%", "<", ">", "&", "^", "|", "!", "~",
+    "?", ":", "=", "(", ")", ",",
+];
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+pub fn evaluate(shell: &mut Shell, expr: &str) -> Result<i64, ArithError> {
+    evaluate_nested(shell, expr, 0)
+}
+
+fn evaluate_nested(shell: &mut Shell, expr: &str, depth: usize) -> Result<i64, ArithError> {
+    if depth > MAX_RECURSION {
+        return Err(ArithError::Recursion(expr.to_string()));
+    }
+
+    let mut parser = Parser {
+        source: expr,
+        tokens: tokenize(expr)?,
+        pos: 0,
+    };
+
+    if parser.tokens.is_empty() {
+        return Ok(0);
+    }
+
+    let ast = parser.comma()?;
+
+    if let Some(token) = parser.tokens.get(parser.pos) {
+        return Err(parser.error_at(token));
+    }
+
+    eval(shell, &ast, expr, depth)
+}
+
+fn tokenize(expr: &str) -> Result<Vec<Token>, ArithError> {
+    let mut tokens = Vec::new();
+    let mut rest = expr;
+
+    while let Some(c) = rest.chars().next() {
+        if c.is_whitespace() {
+            rest = &rest[c.len_utf8()..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '#' && c != '_' && c != '@')
+                .unwrap_or(rest.len());
+
+            let literal = &rest[..end];
+            let value = parse_number(literal)
+                .ok_or_else(|| ArithError::Syntax(expr.to_string(), literal.to_string()))?;
+
+            tokens.push(Token::Num(value));
+            rest = &rest[end..];
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        } else {
+            let op = OPERATORS
+                .iter()
+                .find(|op| rest.starts_with(*op))
+                .ok_or_else(|| ArithError::Syntax(expr.to_string(), rest.to_string()))?;
+
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        }
+    }
+
+    Ok(tokens)
+}
+
+fn parse_number(literal: &str) -> Option<i64> {
+    if let Some((base, digits)) = literal.split_once('#') {
+        let base: u32 = base.parse().ok()?;
+
+        if !(2..=64).contains(&base) {
+            return None;
+        }
+
+        let mut value: i64 = 0;
+
+        for c in digits.chars() {
+            let digit = match c {
+                '0'..='9' => c as u32 - '0' as u32,
+                'a'..='z' => c as u32 - 'a' as u32 + 10,
+                'A'..='Z' if base <= 36 => c as u32 - 'A' as u32 + 10,
+                'A'..='Z' => c as u32 - 'A' as u32 + 36,
+                '@' => 62,
+                '_' => 63,
+                _ => return None,
+            };
+
+            if digit >= base {
+                return None;
+            }
+
+            value = value.wrapping_mul(base as i64).wrapping_add(digit as i64);
+        }
+
+        return Some(value);
+    }
+
+    if let Some(hex) = literal
+        .strip_prefix("0x")
+        .or_else(|| literal.strip_prefix("0X"))
+    {
+        return u64::from_str_radix(hex, 16).ok().map(|v| v as i64);
+    }
+
+    if literal.len() > 1
+        && let Some(octal) = literal.strip_prefix('0')
+    {
+        return u64::from_str_radix(octal, 8).ok().map(|v| v as i64);
+    }
+
+    literal.parse::<u64>().ok().map(|v| v as i64)
+}
+
+fn token_text(token: &Token) -> String {
+    match token {
+        Token::Num(n) => n.to_string(),
+        Token::Ident(name) => name.clone(),
+        Token::Op(op) => op.to_string(),
+    }
+}
+
+impl Parser<'_> {
+    fn peek_op(&self) -> Option<&'static str> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Op(op)) => Some(op),
+            _ => None,
+        }
+    }
+
+    fn error_at(&self, token: &Token) -> ArithError {
+        let rest: Vec<String> = self.tokens[self.pos..].iter().map(token_text).collect();
+        let text = if rest.is_empty() {
+            token_text(token)
+        } else {
+            rest.join(" ")
+        };
+
+        ArithError::Syntax(self.source.to_string(), text)
+    }
+
+    fn error(&self) -> ArithError {
+        match self.tokens.get(self.pos) {
+            Some(token) => self.error_at(token),
+            None => ArithError::Syntax(self.source.to_string(), String::new()),
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), ArithError> {
+        if self.peek_op() == Some(op) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn comma(&mut self) -> Result<Expr, ArithError> {
+        let mut expr = self.assignment()?;
+
+        while self.peek_op() == Some(",") {
+            self.pos += 1;
+            let right = self.assignment()?;
+            expr = Expr::Binary(",", Box::new(expr), Box::new(right));
+        }
+
+        Ok(expr)
+    }
+
+    fn assignment(&mut self) -> Result<Expr, ArithError> {
+        if let (Some(Token::Ident(name)), Some(Token::Op(op))) =
+            (self.tokens.get(self.pos), self.tokens.get(self.pos + 1))
+            && matches!(
+                *op,
+                "=" | "+=" | "-=" | "*=" | "/=" | "%=" | "<<=" | ">>=" | "&=" | "^=" | "|="
+            )
+        {
+            let (name, op) = (name.clone(), *op);
+            self.pos += 2;
+
+            let value = self.assignment()?;
+            return Ok(Expr::Assign(name, op, Box::new(value)));
+        }
+
+        let expr = self.conditional()?;
+
+        if let Some(op) = self.peek_op()
+            && op.ends_with('=')
+            && !matches!(op, "==" | "!=" | "<=" | ">=")
+        {
+            return Err(ArithError::NotAVariable(
+                self.source.to_string(),
+                op.to_string(),
+            ));
+        }
+
+        Ok(expr)
+    }
+
+    fn conditional(&mut self) -> Result<Expr, ArithError> {
+        let condition = self.binary(0)?;
+
+        if self.peek_op() != Some("?") {
+            return Ok(condition);
+        }
+
+        self.pos += 1;
+        let then = self.assignment()?;
+        self.expect(":")?;
+        let otherwise = self.assignment()?;
+
+        Ok(Expr::Conditional(
+            Box::new(condition),
+            Box::new(then),
+            Box::new(otherwise),
+        ))
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr, ArithError> {
+        const LEVELS: [&[&str]; 10] = [
+            &["||"],
+            &["&&"],
+            &["|"],
+            &["^"],
+            &["&"],
+            &["==", "!="],
+            &["<", ">", "<=", ">="],
+            &["<<", ">>"],
+            &["+", "-"],
+            &["*", "/", "%"],
+        ];
+
+        if level == LEVELS.len() {
+            return self.power();
+        }
+
+        let mut left = self.binary(level + 1)?;
+
+        while let Some(op) = self.peek_op() {
+            if !LEVELS[level].contains(&op) {
+                break;
+            }
+
+            self.pos += 1;
+            let right = self.binary(level + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+
+        Ok(left)
+    }
+
+    fn power(&mut self) -> Result<Expr, ArithError> {
+        let base = self.unary()?;
+
+        if self.peek_op() == Some("**") {
+            self.pos += 1;
+            let exponent = self.power()?;
+            return Ok(Expr::Binary("**", Box::new(base), Box::new(exponent)));
+        }
+
+        Ok(base)
+    }
+
+    fn unary(&mut self) -> Result<Expr, ArithError> {
+        match self.peek_op() {
+            Some(op @ ("++" | "--")) => {
+                self.pos += 1;
+
+                match self.tokens.get(self.pos) {
+                    Some(Token::Ident(name)) => {
+                        let name = name.clone();
+                        self.pos += 1;
+
+                        Ok(Expr::Increment {
+                            name,
+                            delta: if op == "++" { 1 } else { -1 },
+                            prefix: true,
+                        })
+                    }
+                    _ => Err(self.error()),
+                }
+            }
+            Some(op @ ("!" | "~" | "+" | "-")) => {
+                self.pos += 1;
+                let operand = self.unary()?;
+                Ok(Expr::Unary(op, Box::new(operand)))
+            }
+            _ => self.postfix(),
+        }
+    }
+
+    fn postfix(&mut self) -> Result<Expr, ArithError> {
+        match self.tokens.get(self.pos).cloned() {
+            Some(Token::Num(n)) => {
+                self.pos += 1;
+                Ok(Expr::Num(n))
+            }
+            Some(Token::Ident(name)) => {
+                self.pos += 1;
+
+                match self.peek_op() {
+                    Some(op @ ("++" | "--")) => {
+                        self.pos += 1;
+
+                        Ok(Expr::Increment {
+                            name,
+                            delta: if op == "++" { 1 } else { -1 },
+                            prefix: false,
+                        })
+                    }
+                    _ => Ok(Expr::Var(name)),
+                }
+            }
+            Some(Token::Op("(")) => {
+                self.pos += 1;
+                let expr = self.comma()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            _ => Err(self.error()),
+        }
+    }
+}
+
+fn variable(shell: &mut Shell, name: &str, depth: usize) -> Result<i64, ArithError> {
+    let value = shell.vars.get(name).unwrap_or("").trim().to_string();
+
+    if value.is_empty() {
+        return Ok(0);
+    }
+
+    match parse_number(&value) {
+        Some(n) => Ok(n),
+        None => evaluate_nested(shell, &value, depth + 1),
+    }
+}
+
+fn set_variable(shell: &mut Shell, name: &str, value: i64) {
+    if is_valid_name(name) {
+        shell.vars.set(name, &value.to_string());
+    }
+}
+
+fn apply(op: &str, left: i64, right: i64, source: &str) -> Result<i64, ArithError> {
+    let value = match op {
+        "+" => left.wrapping_add(right),
+        "-" => left.wrapping_sub(right),
+        "*" => left.wrapping_mul(right),
+        "/" | "%" if right == 0 => {
+            return Err(ArithError::DivisionByZero(
+                source.to_string(),
+                right.to_string(),
+            ));
+        }
+        "/" => left.wrapping_div(right),
+        "%" => left.wrapping_rem(right),
+        "**" => {
+            if right < 0 {
+                return Err(ArithError::Syntax(
+                    source.to_string(),
+                    "exponent less than 0".to_string(),
+                ));
+            }
+
+            left.wrapping_pow(right as u32)
+        }
+        "<<" => left.wrapping_shl(right as u32),
+        ">>" => left.wrapping_shr(right as u32),
+        "&" => left & right,
+        "^" => left ^ right,
+        "|" => left | right,
+        "<" => (left < right) as i64,
+        ">" => (left > right) as i64,
+        "<=" => (left <= right) as i64,
+        ">=" => (left >= right) as i64,
+        "==" => (left == right) as i64,
+        "!=" => (left != right) as i64,
+        "," => right,
+        _ => return Err(ArithError::Syntax(source.to_string(), op.to_string())),
+    };
+
+    Ok(value)
+}
+
+fn eval(shell: &mut Shell, expr: &Expr, source: &str, depth: usize) -> Result<i64, ArithError> {
+    match expr {
+        Expr::Num(n) => Ok(*n),
+        Expr::Var(name) => variable(shell, name, depth),
+        Expr::Unary(op, operand) => {
+            let value = eval(shell, operand, source, depth)?;
+
+            Ok(match *op {
+                "!" => (value == 0) as i64,
+                "~" => !value,
+                "-" => value.wrapping_neg(),
+                _ => value,
+            })
+        }
+        Expr::Binary("&&", left, right) => {
+            let result =
+                eval(shell, left, source, depth)? != 0 && eval(shell, right, source, depth)? != 0;
+            Ok(result as i64)
+        }
+        Expr::Binary("||", left, right) => {
+            let result =
+                eval(shell, left, source, depth)? != 0 || eval(shell, right, source, depth)? != 0;
+            Ok(result as i64)
+        }
+        Expr::Binary(op, left, right) => {
+            let left = eval(shell, left, source, depth)?;
+            let right = eval(shell, right, source, depth)?;
+            apply(op, left, right, source)
+        }
+        Expr::Assign(name, op, value) => {
+            let value = eval(shell, value, source, depth)?;
+
+            let value = match op.strip_suffix('=') {
+                Some("") | None => value,
+                Some(op) => apply(op, variable(shell, name, depth)?, value, source)?,
+            };
+
+            set_variable(shell, name, value);
+            Ok(value)
+        }
+        Expr::Increment {
+            name,
+            delta,
+            prefix,
+        } => {
+            let old = variable(shell, name, depth)?;
+            let new = old.wrapping_add(*delta);
+
+            set_variable(shell, name, new);
+            Ok(if *prefix { new } else { old })
+        }
+        Expr::Conditional(condition, then, otherwise) => {
+            if eval(shell, condition, source, depth)? != 0 {
+                eval(shell, then, source, depth)
+            } else {
+                eval(shell, otherwise, source, depth)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(expr: &str) -> i64 {
+        evaluate(&mut Shell::default(), expr).unwrap()
+    }
+
+    #[test]
+    fn binds_operators_by_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), 7);
+        assert_eq!(eval("(1 + 2) * 3"), 9);
+        assert_eq!(eval("10 - 4 - 3"), 3);
+        assert_eq!(eval("2 ** 3 ** 2"), 512);
+        assert_eq!(eval("-2 ** 2"), 4);
+        assert_eq!(eval("7 / 2 + 7 % 2"), 4);
+        assert_eq!(eval("1 << 2 + 1"), 8);
+        assert_eq!(eval("1 < 2 == 1"), 1);
+        assert_eq!(eval("6 & 3 | 8 ^ 1"), 11);
+        assert_eq!(eval("1 || 0 && 0"), 1);
+        assert_eq!(eval("!0 + ~0"), 0);
+        assert_eq!(eval("0 ? 1 : 2 ? 3 : 4"), 3);
+    }
+
+    #[test]
+    fn reads_integer_literals() {
+        assert_eq!(eval(""), 0);
+        assert_eq!(eval("42"), 42);
+        assert_eq!(eval("0x1F"), 31);
+        assert_eq!(eval("017"), 15);
+        assert_eq!(eval("2#1010"), 10);
+        assert_eq!(eval("16#ff"), 255);
+        assert_eq!(eval("36#z"), 35);
+    }
+
+    #[test]
+    fn assigns_and_reads_variables() {
+        let mut shell = Shell::default();
+
+        assert_eq!(evaluate(&mut shell, "x = 5, x *= 2, x + 1").unwrap(), 11);
+        assert_eq!(evaluate(&mut shell, "y = 1, y++ + ++y").unwrap(), 4);
+        assert_eq!(shell.vars.get("x"), Some("10"));
+        assert_eq!(shell.vars.get("y"), Some("3"));
+
+        shell.vars.set("z", "x + 1");
+        assert_eq!(evaluate(&mut shell, "z * 2").unwrap(), 22);
+        assert_eq!(evaluate(&mut shell, "unset_variable").unwrap(), 0);
+    }
+
+    #[test]
+    fn rejects_invalid_expressions() {
+        let mut shell = Shell::default();
+
+        for expr in ["1 / 0", "5 % 0", "1 +", "08", "(1", "3 = 4", "2#2"] {
+            assert!(evaluate(&mut shell, expr).is_err(), "{}", expr);
+        }
+    }
+}
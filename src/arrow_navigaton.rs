@@ -12,7 +12,7 @@ pub enum Direction {
 
 pub fn move_history(
     direction: Direction,
-    history: &Vec<String>,
+    history: &[String],
     input_buffer: &mut String,
     history_index: &mut usize,
     stdout: &mut std::io::Stdout,
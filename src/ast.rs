@@ -0,0 +1,703 @@
+use std::fmt;
+use std::rc::Rc;
+
+use crate::variables::is_valid_name;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplaceMode {
+    First,
+    All,
+    Prefix,
+    Suffix,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParamOp {
+    Length,
+    Default {
+        word: Word,
+        colon: bool,
+    },
+    Assign {
+        word: Word,
+        colon: bool,
+    },
+    Error {
+        word: Word,
+        colon: bool,
+    },
+    Alternative {
+        word: Word,
+        colon: bool,
+    },
+    RemovePrefix {
+        pattern: Word,
+        longest: bool,
+    },
+    RemoveSuffix {
+        pattern: Word,
+        longest: bool,
+    },
+    Replace {
+        pattern: Word,
+        replacement: Word,
+        mode: ReplaceMode,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter {
+    pub name: String,
+    pub index: Option<String>,
+    pub op: Option<Box<ParamOp>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum WordPart {
+    Literal(String),
+    Quoted(String),
+    Param { param: Parameter, quoted: bool },
+    CommandSub { body: String, quoted: bool },
+    Arith { expr: Word, quoted: bool },
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Word {
+    pub parts: Vec<WordPart>,
+}
+
+impl Word {
+    pub fn push_literal(&mut self, c: char) {
+        match self.parts.last_mut() {
+            Some(WordPart::Literal(s)) => s.push(c),
+            _ => self.parts.push(WordPart::Literal(c.to_string())),
+        }
+    }
+
+    pub fn push_quoted(&mut self, s: &str) {
+        match self.parts.last_mut() {
+            Some(WordPart::Quoted(q)) => q.push_str(s),
+            _ => self.parts.push(WordPart::Quoted(s.to_string())),
+        }
+    }
+
+    pub fn expression(&self) -> String {
+        let mut text = String::new();
+        let _ = write_parts(&mut text, &self.parts, Quoting::Raw);
+        text
+    }
+
+    pub fn as_assignment(&self) -> Option<Assignment> {
+        let Some(WordPart::Literal(first)) = self.parts.first() else {
+            return None;
+        };
+
+        let (name, rest) = first.split_once('=')?;
+
+        if !is_valid_name(name) {
+            return None;
+        }
+
+        let mut value = Word::default();
+
+        if !rest.is_empty() {
+            value.parts.push(WordPart::Literal(rest.to_string()));
+        }
+
+        value.parts.extend(self.parts[1..].iter().cloned());
+
+        Some(Assignment {
+            name: name.to_string(),
+            value,
+        })
+    }
+}
+
+impl fmt::Display for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_parts(f, &self.parts, Quoting::Word)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Quoting {
+    Word,
+    DoubleQuotes,
+    HereDoc,
+    Raw,
+}
+
+fn is_quoted(part: &WordPart) -> bool {
+    match part {
+        WordPart::Literal(_) => false,
+        WordPart::Quoted(_) => true,
+        WordPart::Param { quoted, .. }
+        | WordPart::CommandSub { quoted, .. }
+        | WordPart::Arith { quoted, .. } => *quoted,
+    }
+}
+
+fn escape(text: &str, special: &[char]) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if special.contains(&c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn write_parts(f: &mut impl fmt::Write, parts: &[WordPart], quoting: Quoting) -> fmt::Result {
+    let mut index = 0;
+
+    while index < parts.len() {
+        if quoting == Quoting::Word && is_quoted(&parts[index]) {
+            let end = parts[index..]
+                .iter()
+                .position(|part| !is_quoted(part))
+                .map_or(parts.len(), |offset| index + offset);
+
+            match &parts[index..end] {
+                [WordPart::Quoted(text)] if text.contains(['$', '`', '\\', '"']) => {
+                    write!(f, "'{}'", text.replace('\'', "'\\''"))?
+                }
+                run => {
+                    write!(f, "\"")?;
+                    write_parts(f, run, Quoting::DoubleQuotes)?;
+                    write!(f, "\"")?;
+                }
+            }
+
+            index = end;
+            continue;
+        }
+
+        match &parts[index] {
+            WordPart::Literal(text) => write!(f, "{}", text)?,
+            WordPart::Quoted(text) => match quoting {
+                Quoting::DoubleQuotes => write!(f, "{}", escape(text, &['$', '`', '"', '\\']))?,
+                Quoting::HereDoc => write!(f, "{}", escape(text, &['$', '`', '\\']))?,
+                Quoting::Word | Quoting::Raw => write!(f, "{}", text)?,
+            },
+            WordPart::Param { param, quoted } => write_param(f, param, *quoted)?,
+            WordPart::CommandSub { body, .. } => write!(f, "$({})", body)?,
+            WordPart::Arith { expr, .. } => write!(f, "$(({}))", expr.expression())?,
+        }
+
+        index += 1;
+    }
+
+    Ok(())
+}
+
+fn write_param(f: &mut impl fmt::Write, param: &Parameter, quoted: bool) -> fmt::Result {
+    let name = match &param.index {
+        Some(index) => format!("{}[{}]", param.name, index),
+        None => param.name.clone(),
+    };
+
+    let Some(op) = &param.op else {
+        return write!(f, "${{{}}}", name);
+    };
+
+    let quoting = if quoted {
+        Quoting::DoubleQuotes
+    } else {
+        Quoting::Word
+    };
+
+    match op.as_ref() {
+        ParamOp::Length => write!(f, "${{#{}", name)?,
+        ParamOp::Default { word, colon }
+        | ParamOp::Assign { word, colon }
+        | ParamOp::Error { word, colon }
+        | ParamOp::Alternative { word, colon } => {
+            let symbol = match op.as_ref() {
+                ParamOp::Default { .. } => '-',
+                ParamOp::Assign { .. } => '=',
+                ParamOp::Error { .. } => '?',
+                _ => '+',
+            };
+
+            write!(f, "${{{}{}{}", name, if *colon { ":" } else { "" }, symbol)?;
+            write_parts(f, &word.parts, quoting)?;
+        }
+        ParamOp::RemovePrefix { pattern, longest } => {
+            write!(f, "${{{}{}", name, if *longest { "##" } else { "#" })?;
+            write_parts(f, &pattern.parts, Quoting::Word)?;
+        }
+        ParamOp::RemoveSuffix { pattern, longest } => {
+            write!(f, "${{{}{}", name, if *longest { "%%" } else { "%" })?;
+            write_parts(f, &pattern.parts, Quoting::Word)?;
+        }
+        ParamOp::Replace {
+            pattern,
+            replacement,
+            mode,
+        } => {
+            let symbol = match mode {
+                ReplaceMode::First => "/",
+                ReplaceMode::All => "//",
+                ReplaceMode::Prefix => "/#",
+                ReplaceMode::Suffix => "/%",
+            };
+
+            write!(f, "${{{}{}", name, symbol)?;
+            write_parts(f, &pattern.parts, Quoting::Word)?;
+            write!(f, "/")?;
+            write_parts(f, &replacement.parts, quoting)?;
+        }
+    }
+
+    write!(f, "}}")
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RedirectOp {
+    Write,
+    Clobber,
+    Append,
+    Read,
+    ReadWrite,
+    HereDoc,
+    HereString,
+    DupOutput,
+    DupInput,
+    WriteBoth,
+    AppendBoth,
+}
+
+impl fmt::Display for RedirectOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self {
+            RedirectOp::Write => ">",
+            RedirectOp::Clobber => ">|",
+            RedirectOp::Append => ">>",
+            RedirectOp::Read => "<",
+            RedirectOp::ReadWrite => "<>",
+            RedirectOp::HereDoc => "<<",
+            RedirectOp::HereString => "<<<",
+            RedirectOp::DupOutput => ">&",
+            RedirectOp::DupInput => "<&",
+            RedirectOp::WriteBoth => "&>",
+            RedirectOp::AppendBoth => "&>>",
+        };
+
+        write!(f, "{}", op)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Redirect {
+    pub fd: Option<u32>,
+    pub op: RedirectOp,
+    pub target: Word,
+}
+
+impl Redirect {
+    fn here_document(&self) -> Option<(String, String)> {
+        if self.op != RedirectOp::HereDoc {
+            return None;
+        }
+
+        let parts = &self.target.parts;
+        let quoted = parts
+            .iter()
+            .all(|part| matches!(part, WordPart::Literal(_) | WordPart::Quoted(_)));
+
+        let mut body = String::new();
+        let quoting = if quoted {
+            Quoting::Raw
+        } else {
+            Quoting::HereDoc
+        };
+        let _ = write_parts(&mut body, parts, quoting);
+
+        let mut delimiter = "EOF".to_string();
+        let mut count = 0;
+
+        while body.lines().any(|line| line == delimiter) {
+            count += 1;
+            delimiter = format!("EOF{}", count);
+        }
+
+        let document = format!("{}{}\n", body, delimiter);
+
+        if quoted {
+            delimiter = format!("'{}'", delimiter);
+        }
+
+        Some((delimiter, document))
+    }
+}
+
+impl fmt::Display for Redirect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(fd) = self.fd {
+            write!(f, "{}", fd)?;
+        }
+
+        match self.op {
+            RedirectOp::DupOutput | RedirectOp::DupInput => write!(f, "{}{}", self.op, self.target),
+            RedirectOp::HereDoc => match self.here_document() {
+                Some((delimiter, _)) => write!(f, "{}{}", self.op, delimiter),
+                None => write!(f, "{}", self.op),
+            },
+            _ => write!(f, "{} {}", self.op, self.target),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Assignment {
+    pub name: String,
+    pub value: Word,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SimpleCommand {
+    pub assignments: Vec<Assignment>,
+    pub words: Vec<Word>,
+    pub redirects: Vec<Redirect>,
+}
+
+impl fmt::Display for SimpleCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let assignments = self
+            .assignments
+            .iter()
+            .map(|assignment| format!("{}={}", assignment.name, assignment.value));
+        let words = self.words.iter().map(Word::to_string);
+        let redirects = self.redirects.iter().map(Redirect::to_string);
+
+        let parts: Vec<String> = assignments.chain(words).chain(redirects).collect();
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CaseTerminator {
+    Break,
+    FallThrough,
+    Continue,
+}
+
+impl fmt::Display for CaseTerminator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let terminator = match self {
+            CaseTerminator::Break => ";;",
+            CaseTerminator::FallThrough => ";&",
+            CaseTerminator::Continue => ";;&",
+        };
+
+        write!(f, "{}", terminator)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaseItem {
+    pub patterns: Vec<Word>,
+    pub body: List,
+    pub terminator: CaseTerminator,
+}
+
+impl fmt::Display for CaseItem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let patterns: Vec<String> = self.patterns.iter().map(Word::to_string).collect();
+        write!(f, "{})", patterns.join(" | "))?;
+
+        if !self.body.items.is_empty() {
+            write!(f, " {}", self.body)?;
+        }
+
+        write!(f, " {}", self.terminator)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Compound {
+    If {
+        branches: Vec<(List, List)>,
+        otherwise: Option<List>,
+    },
+    While {
+        until: bool,
+        condition: List,
+        body: List,
+    },
+    For {
+        name: String,
+        words: Option<Vec<Word>>,
+        body: List,
+    },
+    ArithFor {
+        init: Word,
+        condition: Word,
+        update: Word,
+        body: List,
+    },
+    Case {
+        word: Word,
+        items: Vec<CaseItem>,
+    },
+    Group(List),
+    Subshell(List),
+}
+
+impl fmt::Display for Compound {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Compound::If {
+                branches,
+                otherwise,
+            } => {
+                for (index, (condition, body)) in branches.iter().enumerate() {
+                    let keyword = if index == 0 { "if" } else { "elif" };
+                    write!(f, "{} {} then {} ", keyword, condition, body)?;
+                }
+
+                if let Some(otherwise) = otherwise {
+                    write!(f, "else {} ", otherwise)?;
+                }
+
+                write!(f, "fi")
+            }
+            Compound::While {
+                until,
+                condition,
+                body,
+            } => {
+                let keyword = if *until { "until" } else { "while" };
+                write!(f, "{} {} do {} done", keyword, condition, body)
+            }
+            Compound::For { name, words, body } => {
+                write!(f, "for {}", name)?;
+
+                if let Some(words) = words {
+                    write!(f, " in")?;
+
+                    for word in words {
+                        write!(f, " {}", word)?;
+                    }
+                }
+
+                write!(f, "; do {} done", body)
+            }
+            Compound::ArithFor {
+                init,
+                condition,
+                update,
+                body,
+            } => write!(
+                f,
+                "for (({}; {}; {})); do {} done",
+                init.expression().trim(),
+                condition.expression().trim(),
+                update.expression().trim(),
+                body
+            ),
+            Compound::Case { word, items } => {
+                write!(f, "case {} in ", word)?;
+
+                for item in items {
+                    write!(f, "{} ", item)?;
+                }
+
+                write!(f, "esac")
+            }
+            Compound::Group(list) => write!(f, "{{ {} }}", list),
+            Compound::Subshell(list) => {
+                write!(f, "( {} )", list.to_string().trim_end_matches(';'))
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Command {
+    Simple(SimpleCommand),
+    Arith(Word),
+    Compound(Compound, Vec<Redirect>),
+    Function(String, Rc<Command>),
+}
+
+impl Command {
+    pub fn redirect_stderr_to_stdout(&mut self) {
+        let mut target = Word::default();
+        target.push_literal('1');
+
+        let redirect = Redirect {
+            fd: Some(2),
+            op: RedirectOp::DupOutput,
+            target,
+        };
+
+        match self {
+            Command::Simple(command) => command.redirects.push(redirect),
+            Command::Compound(_, redirects) => redirects.push(redirect),
+            Command::Arith(_) | Command::Function(..) => {}
+        }
+    }
+}
+
+impl Command {
+    fn here_documents(&self) -> String {
+        let redirects = match self {
+            Command::Simple(command) => &command.redirects,
+            Command::Compound(_, redirects) => redirects,
+            Command::Arith(_) | Command::Function(..) => return String::new(),
+        };
+
+        redirects
+            .iter()
+            .filter_map(Redirect::here_document)
+            .map(|(_, document)| document)
+            .collect()
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Command::Simple(command) => write!(f, "{}", command),
+            Command::Arith(expr) => write!(f, "(({}))", expr.expression()),
+            Command::Compound(compound, redirects) => {
+                write!(f, "{}", compound)?;
+
+                for redirect in redirects {
+                    write!(f, " {}", redirect)?;
+                }
+
+                Ok(())
+            }
+            Command::Function(name, body) => write!(f, "{} () {}", name, body),
+        }
+    }
+}
+
+pub fn function_definition(name: &str, body: &Command) -> String {
+    let lines: Vec<String> = match body {
+        Command::Compound(Compound::Group(list), redirects) if redirects.is_empty() => {
+            let last = list.items.len().saturating_sub(1);
+
+            list.items
+                .iter()
+                .enumerate()
+                .map(|(index, and_or)| {
+                    let line = match (and_or.background, index == last) {
+                        (true, _) => format!("{} &", and_or),
+                        (false, true) => and_or.to_string(),
+                        (false, false) => format!("{};", and_or),
+                    };
+
+                    with_here_documents(line, &and_or.here_documents())
+                })
+                .collect()
+        }
+        body => vec![with_here_documents(
+            body.to_string(),
+            &body.here_documents(),
+        )],
+    };
+
+    let mut definition = format!("{} () \n{{ \n", name);
+
+    for line in lines {
+        definition.push_str(&format!("    {}\n", line));
+    }
+
+    definition.push('}');
+    definition
+}
+
+fn with_here_documents(line: String, documents: &str) -> String {
+    match documents.strip_suffix('\n') {
+        Some(documents) => format!("{}\n{}", line, documents),
+        None => line,
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Pipeline {
+    pub negated: bool,
+    pub commands: Vec<Command>,
+}
+
+impl fmt::Display for Pipeline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negated {
+            write!(f, "! ")?;
+        }
+
+        let commands: Vec<String> = self.commands.iter().map(Command::to_string).collect();
+        write!(f, "{}", commands.join(" | "))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Connector {
+    And,
+    Or,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct AndOr {
+    pub first: Pipeline,
+    pub rest: Vec<(Connector, Pipeline)>,
+    pub background: bool,
+}
+
+impl AndOr {
+    fn here_documents(&self) -> String {
+        std::iter::once(&self.first)
+            .chain(self.rest.iter().map(|(_, pipeline)| pipeline))
+            .flat_map(|pipeline| &pipeline.commands)
+            .map(Command::here_documents)
+            .collect()
+    }
+}
+
+impl fmt::Display for AndOr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.first)?;
+
+        for (connector, pipeline) in &self.rest {
+            match connector {
+                Connector::And => write!(f, " && {}", pipeline)?,
+                Connector::Or => write!(f, " || {}", pipeline)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct List {
+    pub items: Vec<AndOr>,
+}
+
+impl fmt::Display for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let items: Vec<String> = self
+            .items
+            .iter()
+            .map(|and_or| {
+                let item = match and_or.background {
+                    true => format!("{} &", and_or),
+                    false => format!("{};", and_or),
+                };
+
+                match and_or.here_documents() {
+                    documents if documents.is_empty() => item,
+                    documents => format!("{}\n{}", item, documents),
+                }
+            })
+            .collect();
+
+        write!(f, "{}", items.join(" "))
+    }
+}
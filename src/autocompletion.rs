@@ -20,12 +20,11 @@ pub fn find_completions(prefix: &str) -> Vec<String> {
                 for entry in entries.flatten() {
                     let filename = entry.file_name().into_string().unwrap_or_default();
 
-                    if filename.starts_with(prefix) {
-                        if let Ok(metadata) = entry.metadata() {
-                            if metadata.permissions().mode() & 0o111 != 0 {
-                                matches.push(filename);
-                            }
-                        }
+                    if filename.starts_with(prefix)
+                        && let Ok(metadata) = entry.metadata()
+                        && metadata.permissions().mode() & 0o111 != 0
+                    {
+                        matches.push(filename);
                     }
                 }
             }
@@ -0,0 +1,218 @@
+use crate::ast::{Word, WordPart};
+
+#[derive(Clone)]
+enum Piece {
+    Char(char),
+    Part(WordPart),
+}
+
+pub fn expand_braces(word: &Word) -> Vec<Word> {
+    let mut pieces = Vec::new();
+
+    for part in &word.parts {
+        match part {
+            WordPart::Literal(text) => pieces.extend(text.chars().map(Piece::Char)),
+            part => pieces.push(Piece::Part(part.clone())),
+        }
+    }
+
+    expand(&pieces).into_iter().map(to_word).collect()
+}
+
+fn expand(pieces: &[Piece]) -> Vec<Vec<Piece>> {
+    for start in 0..pieces.len() {
+        if !is_char(&pieces[start], '{') {
+            continue;
+        }
+
+        let Some((end, alternatives)) = alternatives(pieces, start) else {
+            continue;
+        };
+
+        let suffixes = expand(&pieces[end + 1..]);
+        let mut results = Vec::new();
+
+        for alternative in alternatives {
+            for suffix in &suffixes {
+                let mut result = pieces[..start].to_vec();
+                result.extend(alternative.iter().cloned());
+                result.extend(suffix.iter().cloned());
+                results.push(result);
+            }
+        }
+
+        return results;
+    }
+
+    vec![pieces.to_vec()]
+}
+
+fn alternatives(pieces: &[Piece], start: usize) -> Option<(usize, Vec<Vec<Piece>>)> {
+    let mut depth = 0;
+    let mut commas = Vec::new();
+    let mut end = None;
+
+    for (i, piece) in pieces.iter().enumerate().skip(start + 1) {
+        match piece {
+            Piece::Char('{') => depth += 1,
+            Piece::Char('}') if depth == 0 => {
+                end = Some(i);
+                break;
+            }
+            Piece::Char('}') => depth -= 1,
+            Piece::Char(',') if depth == 0 => commas.push(i),
+            _ => {}
+        }
+    }
+
+    let end = end?;
+
+    if commas.is_empty() {
+        let body = pieces[start + 1..end]
+            .iter()
+            .map(|piece| match piece {
+                Piece::Char(c) => Some(*c),
+                Piece::Part(_) => None,
+            })
+            .collect::<Option<String>>()?;
+
+        let items = sequence(&body)?;
+
+        return Some((
+            end,
+            items
+                .into_iter()
+                .map(|item| item.chars().map(Piece::Char).collect())
+                .collect(),
+        ));
+    }
+
+    let mut bounds = vec![start];
+    bounds.extend(commas);
+    bounds.push(end);
+
+    let alternatives = bounds
+        .windows(2)
+        .flat_map(|pair| expand(&pieces[pair[0] + 1..pair[1]]))
+        .collect();
+
+    Some((end, alternatives))
+}
+
+fn sequence(body: &str) -> Option<Vec<String>> {
+    let bounds: Vec<&str> = body.split("..").collect();
+
+    let (first, last, step) = match bounds[..] {
+        [first, last] => (first, last, None),
+        [first, last, step] => (first, last, Some(step.parse::<i64>().ok()?)),
+        _ => return None,
+    };
+
+    let step = step.map_or(1, |step| step.unsigned_abs().max(1)) as usize;
+
+    if let (Ok(from), Ok(to)) = (first.parse::<i64>(), last.parse::<i64>()) {
+        let padded = |bound: &str| {
+            let digits = bound.trim_start_matches('-');
+            digits.len() > 1 && digits.starts_with('0')
+        };
+
+        let width = if padded(first) || padded(last) {
+            first.len().max(last.len())
+        } else {
+            0
+        };
+
+        let values: Vec<i64> = if from <= to {
+            (from..=to).step_by(step).collect()
+        } else {
+            (to..=from).rev().step_by(step).collect()
+        };
+
+        return Some(
+            values
+                .into_iter()
+                .map(|value| format!("{:0width$}", value, width = width))
+                .collect(),
+        );
+    }
+
+    let mut first = first.chars();
+    let mut last = last.chars();
+
+    let (Some(from), None, Some(to), None) = (first.next(), first.next(), last.next(), last.next())
+    else {
+        return None;
+    };
+
+    let chars: Vec<char> = if from <= to {
+        (from..=to).step_by(step).collect()
+    } else {
+        (to..=from).rev().step_by(step).collect()
+    };
+
+    Some(chars.into_iter().map(String::from).collect())
+}
+
+fn is_char(piece: &Piece, expected: char) -> bool {
+    matches!(piece, Piece::Char(c) if *c == expected)
+}
+
+fn to_word(pieces: Vec<Piece>) -> Word {
+    let mut word = Word::default();
+
+    for piece in pieces {
+        match piece {
+            Piece::Char(c) => word.push_literal(c),
+            Piece::Part(part) => word.parts.push(part),
+        }
+    }
+
+    word
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{Token, tokenize};
+
+    fn braces(source: &str) -> Vec<String> {
+        let Ok(tokens) = tokenize(source) else {
+            panic!("cannot tokenize {:?}", source);
+        };
+
+        match &tokens[..] {
+            [Token::Word(word)] => expand_braces(word).iter().map(Word::to_string).collect(),
+            tokens => panic!("expected one word: {:?}", tokens),
+        }
+    }
+
+    #[test]
+    fn numeric_sequences_step_and_pad() {
+        assert_eq!(sequence("1..4").unwrap(), ["1", "2", "3", "4"]);
+        assert_eq!(sequence("3..-1").unwrap(), ["3", "2", "1", "0", "-1"]);
+        assert_eq!(sequence("1..10..3").unwrap(), ["1", "4", "7", "10"]);
+        assert_eq!(sequence("10..1..-4").unwrap(), ["10", "6", "2"]);
+        assert_eq!(sequence("08..11").unwrap(), ["08", "09", "10", "11"]);
+        assert_eq!(sequence("1..010..4").unwrap(), ["001", "005", "009"]);
+    }
+
+    #[test]
+    fn letter_sequences_and_invalid_ranges() {
+        assert_eq!(sequence("a..e..2").unwrap(), ["a", "c", "e"]);
+        assert_eq!(sequence("C..A").unwrap(), ["C", "B", "A"]);
+        assert!(sequence("a..10").is_none());
+        assert!(sequence("ab..c").is_none());
+        assert!(sequence("1..2..x").is_none());
+        assert!(sequence("1").is_none());
+    }
+
+    #[test]
+    fn expands_alternatives_into_separate_words() {
+        assert_eq!(braces("a{b,c}d"), ["abd", "acd"]);
+        assert_eq!(braces("{x,y}{1..2}"), ["x1", "x2", "y1", "y2"]);
+        assert_eq!(braces("{a,b{1,2},}"), ["a", "b1", "b2", ""]);
+        assert_eq!(braces("{a}"), ["{a}"]);
+        assert_eq!(braces("'{a,b}'"), ["\"{a,b}\""]);
+        assert_eq!(braces("{a,b"), ["{a,b"]);
+    }
+}
@@ -0,0 +1,869 @@
+use std::env::{self, set_current_dir};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Read, Write};
+use std::mem::ManuallyDrop;
+use std::os::fd::FromRawFd;
+use std::os::unix::fs::PermissionsExt;
+use std::process::exit;
+
+use crate::ast::function_definition;
+use crate::jobs::JobState;
+use crate::options::{SET_NAMES, SHOPT_NAMES};
+use crate::parser::KEYWORDS;
+use crate::redirection::os_message;
+use crate::shell::{Flow, Shell};
+use crate::signals::{SIGNALS, signal_number};
+use crate::variables::is_valid_name;
+
+pub enum Cmd {
+    Exit,
+    Echo,
+    Type,
+    Run,
+    Pwd,
+    Cd,
+    History,
+    Export,
+    Unset,
+    Shopt,
+    Set,
+    Jobs,
+    Kill,
+    Fg,
+    Bg,
+    Disown,
+    Wait,
+    Break,
+    Continue,
+    Return,
+    Local,
+    Declare,
+}
+
+impl Cmd {
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "exit" => Cmd::Exit,
+            "echo" => Cmd::Echo,
+            "type" => Cmd::Type,
+            "pwd" => Cmd::Pwd,
+            "cd" => Cmd::Cd,
+            "history" => Cmd::History,
+            "export" => Cmd::Export,
+            "unset" => Cmd::Unset,
+            "shopt" => Cmd::Shopt,
+            "set" => Cmd::Set,
+            "jobs" => Cmd::Jobs,
+            "kill" => Cmd::Kill,
+            "fg" => Cmd::Fg,
+            "bg" => Cmd::Bg,
+            "disown" => Cmd::Disown,
+            "wait" => Cmd::Wait,
+            "break" => Cmd::Break,
+            "continue" => Cmd::Continue,
+            "return" => Cmd::Return,
+            "local" => Cmd::Local,
+            "declare" => Cmd::Declare,
+            _ => Cmd::Run,
+        }
+    }
+}
+
+pub fn run_builtin(shell: &mut Shell, cmd: Cmd, name: &str, args: &[String]) -> i32 {
+    let stdout = ManuallyDrop::new(unsafe { File::from_raw_fd(1) });
+    let mut out = BufWriter::new(&*stdout);
+
+    let written = builtin(shell, cmd, args, &mut out).and_then(|status| {
+        out.flush()?;
+        Ok(status)
+    });
+
+    match written {
+        Ok(status) => status,
+        Err(e) => {
+            let _ = out.into_parts();
+            eprintln!("{}: write error: {}", name, os_message(&e));
+            1
+        }
+    }
+}
+
+fn builtin(shell: &mut Shell, cmd: Cmd, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let status = match cmd {
+        Cmd::Exit => {
+            let code = match args.first() {
+                Some(arg) => match arg.parse::<i32>() {
+                    Ok(code) => code & 0xff,
+                    Err(_) => {
+                        eprintln!("exit: {}: numeric argument required", arg);
+                        2
+                    }
+                },
+                None => shell.last_status,
+            };
+
+            if shell.interactive && !shell.subshell {
+                shell.save_history();
+            }
+            exit(code);
+        }
+        Cmd::Echo => {
+            writeln!(out, "{}", args.join(" "))?;
+
+            0
+        }
+        Cmd::Type => {
+            let mut status = 0;
+
+            for name in args {
+                if !type_of(shell, name, out)? {
+                    status = 1;
+                }
+            }
+
+            status
+        }
+        Cmd::Pwd => match env::current_dir() {
+            Ok(path) => {
+                writeln!(out, "{}", path.display())?;
+                0
+            }
+            Err(e) => {
+                eprintln!("pwd: {}", os_message(&e));
+                1
+            }
+        },
+        Cmd::Cd => cd(shell, args, out)?,
+        Cmd::History => history(shell, args, out)?,
+        Cmd::Export => export(shell, args, out)?,
+        Cmd::Unset => unset(shell, args),
+        Cmd::Shopt => shopt(shell, args, out)?,
+        Cmd::Set => set(shell, args, out)?,
+        Cmd::Jobs => jobs(shell, args, out)?,
+        Cmd::Kill => kill(shell, args, out)?,
+        Cmd::Fg => fg(shell, args, out)?,
+        Cmd::Bg => bg(shell, args, out)?,
+        Cmd::Disown => disown(shell, args),
+        Cmd::Wait => wait(shell, args),
+        Cmd::Break => loop_control(shell, "break", args, Flow::Break),
+        Cmd::Continue => loop_control(shell, "continue", args, Flow::Continue),
+        Cmd::Return => return_from_function(shell, args),
+        Cmd::Local => local(shell, args),
+        Cmd::Declare => declare(shell, args, out)?,
+        Cmd::Run => unreachable!(),
+    };
+
+    Ok(status)
+}
+
+fn loop_control(shell: &mut Shell, name: &str, args: &[String], flow: fn(usize) -> Flow) -> i32 {
+    let count = match args.first() {
+        Some(arg) => match arg.parse::<i64>() {
+            Ok(count) if count > 0 => count as usize,
+            Ok(_) => {
+                eprintln!("{}: {}: loop count out of range", name, arg);
+                return 1;
+            }
+            Err(_) => {
+                eprintln!("{}: {}: numeric argument required", name, arg);
+                return 1;
+            }
+        },
+        None => 1,
+    };
+
+    if shell.loop_depth == 0 {
+        eprintln!(
+            "{}: only meaningful in a `for', `while', or `until' loop",
+            name
+        );
+        return 0;
+    }
+
+    shell.flow = Some(flow(count.min(shell.loop_depth)));
+    0
+}
+
+fn return_from_function(shell: &mut Shell, args: &[String]) -> i32 {
+    let code = match args.first() {
+        Some(arg) => match arg.parse::<i32>() {
+            Ok(code) => code & 0xff,
+            Err(_) => {
+                eprintln!("return: {}: numeric argument required", arg);
+                2
+            }
+        },
+        None => shell.last_status,
+    };
+
+    if shell.function_depth == 0 {
+        eprintln!("return: can only `return' from a function or sourced script");
+        return 1;
+    }
+
+    shell.flow = Some(Flow::Return(code));
+    code
+}
+
+fn local(shell: &mut Shell, args: &[String]) -> i32 {
+    if shell.function_depth == 0 {
+        eprintln!("local: can only be used in a function");
+        return 1;
+    }
+
+    declare_variables(shell, "local", args)
+}
+
+fn declare(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let (options, names): (Vec<&String>, Vec<&String>) =
+        args.iter().partition(|arg| arg.starts_with('-'));
+
+    let functions = options.iter().any(|option| option.contains('f'));
+    let names_only = options.iter().any(|option| option.contains('F'));
+
+    if !functions && !names_only {
+        let names: Vec<String> = names.into_iter().cloned().collect();
+        return Ok(declare_variables(shell, "declare", &names));
+    }
+
+    let mut selected: Vec<&String> = if names.is_empty() {
+        shell.functions.keys().collect()
+    } else {
+        names
+    };
+    selected.sort();
+
+    let mut status = 0;
+
+    for name in selected {
+        match shell.functions.get(name) {
+            Some(_) if names_only => writeln!(out, "declare -f {}", name)?,
+            Some(body) => writeln!(out, "{}", function_definition(name, body))?,
+            None => status = 1,
+        }
+    }
+
+    Ok(status)
+}
+
+fn declare_variables(shell: &mut Shell, builtin: &str, args: &[String]) -> i32 {
+    let mut status = 0;
+
+    for arg in args {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg.as_str(), None),
+        };
+
+        if !is_valid_name(name) {
+            eprintln!("{}: `{}': not a valid identifier", builtin, arg);
+            status = 1;
+        } else if shell.function_depth > 0 {
+            shell.vars.local(name, value);
+        } else if let Some(value) = value {
+            shell.vars.set(name, value);
+        }
+    }
+
+    status
+}
+
+fn cd(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let print = args.first().is_some_and(|arg| arg == "-");
+    let target = match args.first().map(String::as_str) {
+        Some("-") => match shell.vars.get("OLDPWD") {
+            Some(oldpwd) => oldpwd.to_string(),
+            None => {
+                eprintln!("cd: OLDPWD not set");
+                return Ok(1);
+            }
+        },
+        Some(target) => target.to_string(),
+        None => match shell.vars.get("HOME") {
+            Some(home) => home.to_string(),
+            None => {
+                eprintln!("cd: HOME not set");
+                return Ok(1);
+            }
+        },
+    };
+
+    let oldpwd = match env::current_dir() {
+        Ok(dir) => dir.display().to_string(),
+        Err(_) => shell.vars.get("PWD").unwrap_or_default().to_string(),
+    };
+
+    if let Err(e) = set_current_dir(&target) {
+        eprintln!("cd: {}: {}", target, os_message(&e));
+        return Ok(1);
+    }
+
+    let pwd = match env::current_dir() {
+        Ok(dir) => dir.display().to_string(),
+        Err(_) => target,
+    };
+
+    if print {
+        writeln!(out, "{}", pwd)?;
+    }
+
+    shell.vars.set("OLDPWD", &oldpwd);
+    shell.vars.set("PWD", &pwd);
+
+    Ok(0)
+}
+
+fn type_of(shell: &Shell, name: &str, out: &mut impl Write) -> io::Result<bool> {
+    if KEYWORDS.contains(&name) {
+        writeln!(out, "{} is a shell keyword", name)?;
+        return Ok(true);
+    }
+
+    if let Some(body) = shell.functions.get(name) {
+        writeln!(out, "{} is a function", name)?;
+        writeln!(out, "{}", function_definition(name, body))?;
+        return Ok(true);
+    }
+
+    let found = match Cmd::parse(name) {
+        Cmd::Run => {
+            if let Some(full_path) = find_in_path(shell.vars.get("PATH"), name) {
+                writeln!(out, "{} is {}", name, full_path)?;
+                true
+            } else {
+                writeln!(out, "{}: not found", name)?;
+                false
+            }
+        }
+        _ => {
+            writeln!(out, "{} is a shell builtin", name)?;
+            true
+        }
+    };
+
+    Ok(found)
+}
+
+pub fn find_in_path(path_var: Option<&str>, name: &str) -> Option<String> {
+    let path_var = path_var?;
+
+    for path in path_var.split(':') {
+        let full_path = format!("{}/{}", path, name);
+
+        if let Ok(metadata) = fs::metadata(&full_path)
+            && metadata.is_file()
+            && metadata.permissions().mode() & 0o111 != 0
+        {
+            return Some(full_path);
+        }
+    }
+
+    None
+}
+
+fn export(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    if args.is_empty() || args[0] == "-p" {
+        for (name, value) in shell.vars.exported_names() {
+            match value {
+                Some(value) => writeln!(out, "declare -x {}=\"{}\"", name, escape_value(&value))?,
+                None => writeln!(out, "declare -x {}", name)?,
+            }
+        }
+        return Ok(0);
+    }
+
+    let mut status = 0;
+
+    for arg in args {
+        let (name, value) = match arg.split_once('=') {
+            Some((name, value)) => (name, Some(value)),
+            None => (arg.as_str(), None),
+        };
+
+        if is_valid_name(name) {
+            shell.vars.export(name, value);
+        } else {
+            eprintln!("export: `{}': not a valid identifier", arg);
+            status = 1;
+        }
+    }
+
+    Ok(status)
+}
+
+fn unset(shell: &mut Shell, args: &[String]) -> i32 {
+    let mut status = 0;
+    let functions = args.iter().any(|arg| arg == "-f");
+
+    for name in args.iter().filter(|arg| *arg != "-v" && *arg != "-f") {
+        if functions {
+            shell.functions.remove(name);
+        } else if is_valid_name(name) {
+            shell.vars.unset(name);
+        } else {
+            eprintln!("unset: `{}': not a valid identifier", name);
+            status = 1;
+        }
+    }
+
+    status
+}
+
+fn shopt(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let mut setting = None;
+    let mut print = false;
+    let mut quiet = false;
+    let mut names = Vec::new();
+
+    for arg in args {
+        match arg.as_str() {
+            "-s" => setting = Some(true),
+            "-u" => setting = Some(false),
+            "-p" => print = true,
+            "-q" => quiet = true,
+            flag if flag.starts_with('-') => {
+                eprintln!("shopt: {}: invalid option", flag);
+                eprintln!("shopt: usage: shopt [-pqsu] [optname ...]");
+                return Ok(2);
+            }
+            name => names.push(name),
+        }
+    }
+
+    for name in &names {
+        if shell.options.shopt(name).is_none() {
+            eprintln!("shopt: {}: invalid shell option name", name);
+            return Ok(1);
+        }
+    }
+
+    if let Some(value) = setting
+        && !names.is_empty()
+    {
+        for name in names {
+            if let Some(option) = shell.options.shopt(name) {
+                *option = value;
+            }
+        }
+        return Ok(0);
+    }
+
+    let listed = if names.is_empty() {
+        SHOPT_NAMES.to_vec()
+    } else {
+        names
+    };
+
+    let mut status = 0;
+
+    for name in listed {
+        let enabled = shell.options.shopt(name).is_some_and(|option| *option);
+
+        if !enabled {
+            status = 1;
+        }
+
+        if quiet || setting.is_some_and(|value| value != enabled) {
+            continue;
+        }
+
+        if print {
+            writeln!(out, "shopt {} {}", if enabled { "-s" } else { "-u" }, name)?;
+        } else {
+            writeln!(out, "{:<15}\t{}", name, if enabled { "on" } else { "off" })?;
+        }
+    }
+
+    Ok(if setting.is_some() { 0 } else { status })
+}
+
+fn set(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let mut args = args.iter();
+
+    while let Some(arg) = args.next() {
+        let enable = arg.starts_with('-');
+
+        match arg.as_str() {
+            "-o" | "+o" => {
+                let Some(name) = args.next() else {
+                    for name in SET_NAMES {
+                        let enabled = shell.options.set(name).is_some_and(|option| *option);
+
+                        if enable {
+                            writeln!(out, "{:<15}\t{}", name, if enabled { "on" } else { "off" })?;
+                        } else {
+                            writeln!(out, "set {}o {}", if enabled { "-" } else { "+" }, name)?;
+                        }
+                    }
+                    return Ok(0);
+                };
+
+                match shell.options.set(name) {
+                    Some(option) => *option = enable,
+                    None => {
+                        eprintln!("set: {}: invalid option name", name);
+                        return Ok(1);
+                    }
+                }
+            }
+            "-C" | "+C" => shell.options.noclobber = enable,
+            "-f" | "+f" => shell.options.noglob = enable,
+            _ => {
+                eprintln!("set: {}: invalid option", arg);
+                return Ok(2);
+            }
+        }
+    }
+
+    Ok(0)
+}
+
+fn jobs(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let show_pid = args.iter().any(|arg| arg == "-l");
+    let pids_only = args.iter().any(|arg| arg == "-p");
+    let specs: Vec<&String> = args.iter().filter(|arg| !arg.starts_with('-')).collect();
+
+    shell.jobs.update();
+
+    let ids = if specs.is_empty() {
+        shell.jobs.ids()
+    } else {
+        let mut ids = Vec::new();
+
+        for spec in specs {
+            match shell.jobs.find(spec) {
+                Ok(id) => ids.push(id),
+                Err(e) => {
+                    eprintln!("jobs: {}", e);
+                    return Ok(1);
+                }
+            }
+        }
+
+        ids
+    };
+
+    for id in ids {
+        let Some(job) = shell.jobs.get(id) else {
+            continue;
+        };
+
+        if pids_only {
+            writeln!(out, "{}", job.processes[0].pid)?;
+        } else {
+            writeln!(out, "{}", shell.jobs.describe(job, show_pid))?;
+        }
+
+        if matches!(job.state(), JobState::Done(_)) {
+            shell.jobs.remove(id);
+        }
+    }
+
+    Ok(0)
+}
+
+fn job_ids(shell: &Shell, name: &str, specs: &[String]) -> Option<Vec<usize>> {
+    if specs.is_empty() {
+        return match shell.jobs.current() {
+            Some(id) => Some(vec![id]),
+            None => {
+                eprintln!("{}: current: no such job", name);
+                None
+            }
+        };
+    }
+
+    let mut ids = Vec::new();
+
+    for spec in specs {
+        match shell.jobs.find(spec) {
+            Ok(id) => ids.push(id),
+            Err(e) => {
+                eprintln!("{}: {}", name, e);
+                return None;
+            }
+        }
+    }
+
+    Some(ids)
+}
+
+fn fg(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    if !shell.jobs.job_control {
+        eprintln!("fg: no job control");
+        return Ok(1);
+    }
+
+    let Some(ids) = job_ids(shell, "fg", &args[..args.len().min(1)]) else {
+        return Ok(1);
+    };
+
+    let id = ids[0];
+
+    if let Err(e) = shell.jobs.continue_job(id) {
+        eprintln!("fg: {}", e);
+        return Ok(1);
+    }
+
+    let Some(job) = shell.jobs.remove(id) else {
+        return Ok(1);
+    };
+
+    writeln!(out, "{}", job.command)?;
+    out.flush()?;
+
+    Ok(shell.jobs.foreground(job).last().copied().unwrap_or(0))
+}
+
+fn bg(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    if !shell.jobs.job_control {
+        eprintln!("bg: no job control");
+        return Ok(1);
+    }
+
+    let Some(ids) = job_ids(shell, "bg", args) else {
+        return Ok(1);
+    };
+
+    for id in ids {
+        if let Err(e) = shell.jobs.continue_job(id) {
+            eprintln!("bg: {}", e);
+            return Ok(1);
+        }
+
+        if let Some(job) = shell.jobs.get(id) {
+            writeln!(out, "[{}]{} {} &", id, shell.jobs.marker(id), job.command)?;
+        }
+    }
+
+    Ok(0)
+}
+
+fn disown(shell: &mut Shell, args: &[String]) -> i32 {
+    let ids = match args.first().map(String::as_str) {
+        Some("-a") => shell.jobs.ids(),
+        Some("-r") => shell
+            .jobs
+            .ids()
+            .into_iter()
+            .filter(|&id| {
+                shell
+                    .jobs
+                    .get(id)
+                    .is_some_and(|job| job.state() == JobState::Running)
+            })
+            .collect(),
+        _ => match job_ids(shell, "disown", args) {
+            Some(ids) => ids,
+            None => return 1,
+        },
+    };
+
+    for id in ids {
+        shell.jobs.remove(id);
+    }
+
+    0
+}
+
+fn wait(shell: &mut Shell, args: &[String]) -> i32 {
+    if args.is_empty() {
+        for id in shell.jobs.ids() {
+            shell.jobs.wait(id);
+        }
+
+        return 0;
+    }
+
+    let mut status = 0;
+
+    for arg in args {
+        let id = if arg.starts_with('%') {
+            shell.jobs.find(arg)
+        } else {
+            arg.parse::<libc::pid_t>()
+                .ok()
+                .and_then(|pid| shell.jobs.find_pid(pid))
+                .ok_or_else(|| format!("pid {} is not a child of this shell", arg))
+        };
+
+        status = match id {
+            Ok(id) => shell.jobs.wait(id).unwrap_or(127),
+            Err(e) => {
+                eprintln!("wait: {}", e);
+                127
+            }
+        };
+    }
+
+    status
+}
+
+fn kill(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let mut signal = libc::SIGTERM;
+    let mut args = args;
+
+    match args.first().map(String::as_str) {
+        Some("-l") => {
+            let names: Vec<String> = SIGNALS
+                .iter()
+                .map(|(name, number)| format!("{}) SIG{}", number, name))
+                .collect();
+            writeln!(out, "{}", names.join("\t"))?;
+            return Ok(0);
+        }
+        Some("-s" | "-n") => {
+            let Some(name) = args.get(1) else {
+                eprintln!("kill: {}: option requires an argument", args[0]);
+                return Ok(2);
+            };
+
+            match signal_number(name) {
+                Some(number) => signal = number,
+                None => {
+                    eprintln!("kill: {}: invalid signal specification", name);
+                    return Ok(1);
+                }
+            }
+
+            args = &args[2..];
+        }
+        Some(option) if option.len() > 1 && option.starts_with('-') => {
+            match signal_number(&option[1..]) {
+                Some(number) => signal = number,
+                None => {
+                    eprintln!("kill: {}: invalid signal specification", &option[1..]);
+                    return Ok(1);
+                }
+            }
+
+            args = &args[1..];
+        }
+        _ => {}
+    }
+
+    if args.is_empty() {
+        eprintln!("kill: usage: kill [-s sigspec | -n signum | -sigspec] pid | jobspec ...");
+        return Ok(2);
+    }
+
+    let mut status = 0;
+
+    for target in args {
+        if target.starts_with('%') {
+            let sent = shell.jobs.find(target).and_then(|id| {
+                let sent = match signal {
+                    libc::SIGCONT => shell.jobs.continue_job(id),
+                    signal => shell.jobs.signal(id, signal),
+                };
+
+                sent.map_err(|_| format!("{}: no such job", target))
+            });
+
+            if let Err(e) = sent {
+                eprintln!("kill: {}", e);
+                status = 1;
+            }
+            continue;
+        }
+
+        let Ok(pid) = target.parse::<libc::pid_t>() else {
+            eprintln!("kill: {}: arguments must be process or job IDs", target);
+            status = 1;
+            continue;
+        };
+
+        if unsafe { libc::kill(pid, signal) } == -1 {
+            eprintln!("kill: ({}) - No such process", pid);
+            status = 1;
+        }
+    }
+
+    Ok(status)
+}
+
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in value.chars() {
+        if matches!(c, '"' | '\\' | '$' | '`') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn history(shell: &mut Shell, args: &[String], out: &mut impl Write) -> io::Result<i32> {
+    let local_history = &mut shell.history;
+
+    let Some(first) = args.first() else {
+        for (i, cmd) in local_history.iter().enumerate() {
+            writeln!(out, "  {}  {}", i + 1, cmd)?;
+        }
+        return Ok(0);
+    };
+
+    if let Ok(limiter) = first.parse::<usize>() {
+        for (i, last_cmd) in local_history.iter().enumerate().rev().take(limiter).rev() {
+            writeln!(out, "  {}  {}", i + 1, last_cmd)?;
+        }
+        return Ok(0);
+    }
+
+    let Some(path) = args.get(1) else {
+        eprintln!("history: {}: option requires an argument", first);
+        return Ok(1);
+    };
+
+    let result = match first.as_str() {
+        "-r" => File::open(path).and_then(|mut file| {
+            let mut contents = String::new();
+            file.read_to_string(&mut contents)?;
+
+            for cmd in contents.lines() {
+                local_history.push(cmd.to_string());
+            }
+
+            Ok(())
+        }),
+        "-w" => OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+            .and_then(|mut file| {
+                for lines in local_history.iter() {
+                    writeln!(file, "{}", lines)?;
+                }
+
+                Ok(())
+            }),
+        "-a" => OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(path)
+            .and_then(|mut file| {
+                let mut iter =
+                    local_history.split_inclusive(|cmd_block| cmd_block.contains("history -a"));
+
+                for lines in iter.next_back().unwrap_or_default() {
+                    writeln!(file, "{}", lines)?;
+                }
+
+                Ok(())
+            }),
+        _ => {
+            eprintln!("history: {}: invalid option", first);
+            return Ok(1);
+        }
+    };
+
+    if let Err(e) = result {
+        eprintln!("history: {}: {}", path, os_message(&e));
+        return Ok(1);
+    }
+
+    Ok(0)
+}
@@ -0,0 +1,531 @@
+use std::fs::File;
+use std::io;
+use std::mem;
+use std::os::fd::AsRawFd;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::process::Command;
+use std::rc::Rc;
+
+use crate::ast::{
+    AndOr, Assignment, CaseItem, CaseTerminator, Command as AstCommand, Compound, Connector, List,
+    Pipeline, SimpleCommand, Word,
+};
+use crate::builtins::{Cmd, find_in_path, run_builtin};
+use crate::expansion::{
+    ExpansionError, arithmetic_expansion, expand_pattern, expand_word, expand_words,
+};
+use crate::jobs::{Job, Jobs};
+use crate::pattern::Pattern;
+use crate::pipeline::run_pipeline;
+use crate::process::{dup2, exit_child, fork, join_process_group};
+use crate::redirection::{SavedFds, apply_redirections, os_message};
+use crate::shell::{Flow, Shell};
+use crate::signals::reset_signals;
+
+pub fn run_list(shell: &mut Shell, list: &List) -> i32 {
+    let mut status = 0;
+
+    for and_or in &list.items {
+        if shell.flow.is_some() {
+            break;
+        }
+
+        status = if and_or.background {
+            run_background(shell, and_or)
+        } else {
+            run_and_or(shell, and_or)
+        };
+    }
+
+    status
+}
+
+fn run_background(shell: &mut Shell, and_or: &AndOr) -> i32 {
+    let job_control = shell.jobs.job_control;
+
+    match fork() {
+        Ok(0) => {
+            if job_control {
+                join_process_group(0, false);
+            } else if let Ok(null) = File::open("/dev/null") {
+                let _ = dup2(null.as_raw_fd(), 0);
+            }
+
+            reset_signals();
+            shell.subshell = true;
+            shell.jobs = Jobs::default();
+
+            let status = run_and_or(shell, and_or);
+            exit_child(status);
+        }
+        Ok(pid) => {
+            if job_control {
+                unsafe { libc::setpgid(pid, pid) };
+            }
+
+            let id = shell.jobs.add(Job::new(pid, &[pid], &and_or.to_string()));
+            shell.last_background = Some(pid);
+
+            if shell.interactive && !shell.subshell {
+                eprintln!("[{}] {}", id, pid);
+            }
+
+            0
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn run_and_or(shell: &mut Shell, and_or: &AndOr) -> i32 {
+    let mut status = run_pipeline_or_command(shell, &and_or.first);
+
+    for (connector, pipeline) in &and_or.rest {
+        if shell.flow.is_some() {
+            break;
+        }
+
+        let should_run = match connector {
+            Connector::And => status == 0,
+            Connector::Or => status != 0,
+        };
+
+        if should_run {
+            status = run_pipeline_or_command(shell, pipeline);
+        }
+    }
+
+    status
+}
+
+fn run_pipeline_or_command(shell: &mut Shell, pipeline: &Pipeline) -> i32 {
+    let statuses = if pipeline.commands.len() > 1 {
+        run_pipeline(shell, pipeline)
+    } else if let Some(command) = pipeline.commands.first() {
+        vec![run_command(shell, command)]
+    } else {
+        vec![0]
+    };
+
+    let status = if shell.options.pipefail {
+        statuses.iter().rev().find(|&&status| status != 0)
+    } else {
+        statuses.last()
+    };
+
+    shell.last_status = match (*status.unwrap_or(&0), pipeline.negated) {
+        (0, true) => 1,
+        (_, true) => 0,
+        (status, false) => status,
+    };
+    shell.pipestatus = statuses;
+    shell.last_status
+}
+
+pub fn run_command(shell: &mut Shell, command: &AstCommand) -> i32 {
+    match command {
+        AstCommand::Simple(command) => run_simple_command(shell, command),
+        AstCommand::Arith(expr) => run_arith_command(shell, expr),
+        AstCommand::Compound(compound, redirects) => {
+            let mut saved = SavedFds::default();
+
+            let status = match apply_redirections(shell, redirects, &mut saved) {
+                Ok(()) => run_compound(shell, compound),
+                Err(e) => {
+                    eprintln!("{}: {}", shell.arg0, e);
+                    1
+                }
+            };
+
+            saved.restore();
+            status
+        }
+        AstCommand::Function(name, body) => {
+            shell.functions.insert(name.clone(), Rc::clone(body));
+            0
+        }
+    }
+}
+
+fn run_function(shell: &mut Shell, body: &AstCommand, args: &[String]) -> i32 {
+    let positional = mem::replace(&mut shell.positional, args.to_vec());
+    let loop_depth = mem::take(&mut shell.loop_depth);
+
+    shell.vars.push_scope();
+    shell.function_depth += 1;
+
+    let mut status = run_command(shell, body);
+
+    if let Some(Flow::Return(code)) = shell.flow {
+        shell.flow = None;
+        status = code;
+    }
+
+    shell.function_depth -= 1;
+    shell.vars.pop_scope();
+
+    shell.loop_depth = loop_depth;
+    shell.positional = positional;
+    status
+}
+
+fn run_compound(shell: &mut Shell, compound: &Compound) -> i32 {
+    match compound {
+        Compound::If {
+            branches,
+            otherwise,
+        } => {
+            for (condition, body) in branches {
+                if run_list(shell, condition) == 0 {
+                    return run_list(shell, body);
+                }
+
+                if shell.flow.is_some() {
+                    return 0;
+                }
+            }
+
+            match otherwise {
+                Some(otherwise) => run_list(shell, otherwise),
+                None => 0,
+            }
+        }
+        Compound::While {
+            until,
+            condition,
+            body,
+        } => run_loop(shell, |shell| {
+            let status = run_list(shell, condition);
+            (shell.flow.is_some() || (status == 0) != *until).then_some(body)
+        }),
+        Compound::For { name, words, body } => {
+            let values = match words {
+                Some(words) => match expand_words(shell, words) {
+                    Ok(values) => values,
+                    Err(e) => return expansion_failed(shell, e),
+                },
+                None => shell.positional.clone(),
+            };
+
+            let mut values = values.into_iter();
+
+            run_loop(shell, |shell| {
+                let value = values.next()?;
+                shell.vars.set(name, &value);
+                Some(body)
+            })
+        }
+        Compound::ArithFor {
+            init,
+            condition,
+            update,
+            body,
+        } => {
+            if arith_true(shell, init).is_none() {
+                return 1;
+            }
+
+            let mut first = true;
+
+            run_loop(shell, |shell| {
+                if !mem::take(&mut first) {
+                    arith_true(shell, update)?;
+                }
+
+                arith_true(shell, condition)?.then_some(body)
+            })
+        }
+        Compound::Case { word, items } => run_case(shell, word, items),
+        Compound::Group(list) => run_list(shell, list),
+        Compound::Subshell(list) => run_subshell(shell, compound, list),
+    }
+}
+
+fn run_subshell(shell: &mut Shell, compound: &Compound, list: &List) -> i32 {
+    let job_control = shell.jobs.job_control;
+
+    match fork() {
+        Ok(0) => {
+            if job_control {
+                join_process_group(0, true);
+            }
+
+            reset_signals();
+            shell.subshell = true;
+            shell.jobs = Jobs::default();
+
+            let status = run_list(shell, list);
+            exit_child(status);
+        }
+        Ok(pid) => {
+            if job_control {
+                unsafe { libc::setpgid(pid, pid) };
+            }
+
+            let job = Job::new(pid, &[pid], &compound.to_string());
+            shell.jobs.foreground(job)[0]
+        }
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn run_case(shell: &mut Shell, word: &Word, items: &[CaseItem]) -> i32 {
+    let subject = match expand_word(shell, word) {
+        Ok(subject) => subject,
+        Err(e) => {
+            eprintln!("{}", e);
+            return 1;
+        }
+    };
+
+    let mut status = 0;
+    let mut fall_through = false;
+
+    for item in items {
+        if !fall_through {
+            match case_matches(shell, item, &subject) {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 1;
+                }
+            }
+        }
+
+        status = run_list(shell, &item.body);
+
+        if shell.flow.is_some() {
+            break;
+        }
+
+        match item.terminator {
+            CaseTerminator::Break => break,
+            CaseTerminator::FallThrough => fall_through = true,
+            CaseTerminator::Continue => fall_through = false,
+        }
+    }
+
+    status
+}
+
+fn case_matches(shell: &mut Shell, item: &CaseItem, subject: &str) -> Result<bool, ExpansionError> {
+    for pattern in &item.patterns {
+        let pattern = Pattern::new(&expand_pattern(shell, pattern)?, shell.options.extglob);
+
+        if pattern.matches(subject) {
+            return Ok(true);
+        }
+    }
+
+    Ok(false)
+}
+
+fn run_loop<'a>(shell: &mut Shell, mut next: impl FnMut(&mut Shell) -> Option<&'a List>) -> i32 {
+    let mut status = 0;
+
+    shell.loop_depth += 1;
+
+    while let Some(body) = next(shell) {
+        if shell.flow.is_none() {
+            status = run_list(shell, body);
+        }
+
+        match shell.flow {
+            None => {}
+            Some(Flow::Continue(1)) => shell.flow = None,
+            Some(Flow::Continue(count)) => {
+                shell.flow = Some(Flow::Continue(count - 1));
+                break;
+            }
+            Some(Flow::Break(count)) => {
+                shell.flow = (count > 1).then(|| Flow::Break(count - 1));
+                break;
+            }
+            Some(Flow::Return(_) | Flow::Abort) => break,
+        }
+    }
+
+    shell.loop_depth -= 1;
+    status
+}
+
+fn arith_true(shell: &mut Shell, expr: &Word) -> Option<bool> {
+    if expr.parts.is_empty() {
+        return Some(true);
+    }
+
+    match arithmetic_expansion(shell, expr) {
+        Ok(value) => Some(value != "0"),
+        Err(e) => {
+            eprintln!("{}", e);
+            None
+        }
+    }
+}
+
+pub fn run_arith_command(shell: &mut Shell, expr: &Word) -> i32 {
+    match arithmetic_expansion(shell, expr) {
+        Ok(value) if value != "0" => 0,
+        Ok(_) => 1,
+        Err(e) => {
+            eprintln!("{}", e);
+            1
+        }
+    }
+}
+
+fn expansion_failed(shell: &mut Shell, error: ExpansionError) -> i32 {
+    if matches!(error, ExpansionError::NoMatch(_)) {
+        shell.flow = Some(Flow::Abort);
+    }
+
+    eprintln!("{}", error);
+    1
+}
+
+fn run_simple_command(shell: &mut Shell, command: &SimpleCommand) -> i32 {
+    shell.substitution_status = None;
+
+    let parts = match expand_words(shell, &command.words) {
+        Ok(parts) => parts,
+        Err(e) => return expansion_failed(shell, e),
+    };
+
+    let mut saved = SavedFds::default();
+
+    let status = match apply_redirections(shell, &command.redirects, &mut saved) {
+        Ok(()) => execute(shell, &command.assignments, &parts),
+        Err(e) => {
+            eprintln!("{}: {}", shell.arg0, e);
+            1
+        }
+    };
+
+    saved.restore();
+    status
+}
+
+fn execute(shell: &mut Shell, assignments: &[Assignment], parts: &[String]) -> i32 {
+    if parts.is_empty() {
+        for assignment in assignments {
+            match expand_word(shell, &assignment.value) {
+                Ok(value) => shell.vars.set(&assignment.name, &value),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return 1;
+                }
+            }
+        }
+
+        return shell.substitution_status.unwrap_or(0);
+    }
+
+    if let Some(body) = shell.functions.get(&parts[0]).cloned() {
+        return with_assignments(shell, assignments, |shell| {
+            run_function(shell, &body, &parts[1..])
+        });
+    }
+
+    match Cmd::parse(&parts[0]) {
+        Cmd::Run => with_assignments(shell, assignments, |shell| {
+            let job_control = shell.jobs.job_control;
+
+            let program = if parts[0].contains('/') {
+                parts[0].clone()
+            } else {
+                match find_in_path(shell.vars.get("PATH"), &parts[0]) {
+                    Some(program) => program,
+                    None => {
+                        eprintln!("{}: command not found", parts[0]);
+                        return 127;
+                    }
+                }
+            };
+
+            let mut command = Command::new(program);
+            command
+                .arg0(&parts[0])
+                .args(&parts[1..])
+                .env_clear()
+                .envs(shell.vars.exported());
+
+            unsafe {
+                command.pre_exec(move || {
+                    if job_control {
+                        join_process_group(0, true);
+                    }
+
+                    reset_signals();
+                    Ok(())
+                });
+            }
+
+            match command.spawn() {
+                Ok(child) => {
+                    let pid = child.id() as libc::pid_t;
+
+                    if job_control {
+                        unsafe { libc::setpgid(pid, pid) };
+                    }
+
+                    let job = Job::new(pid, &[pid], &parts.join(" "));
+                    shell.jobs.foreground(job)[0]
+                }
+                Err(e) => {
+                    let e = match Path::new(&parts[0]).is_dir() {
+                        true => io::Error::from_raw_os_error(libc::EISDIR),
+                        false => e,
+                    };
+
+                    eprintln!("{}: {}: {}", shell.arg0, parts[0], os_message(&e));
+
+                    match e.kind() {
+                        io::ErrorKind::NotFound => 127,
+                        _ => 126,
+                    }
+                }
+            }
+        }),
+        cmd => with_assignments(shell, assignments, |shell| {
+            run_builtin(shell, cmd, &parts[0], &parts[1..])
+        }),
+    }
+}
+
+fn with_assignments(
+    shell: &mut Shell,
+    assignments: &[Assignment],
+    run: impl FnOnce(&mut Shell) -> i32,
+) -> i32 {
+    let mut saved = Vec::new();
+    let mut failed = false;
+
+    for assignment in assignments {
+        let value = match expand_word(shell, &assignment.value) {
+            Ok(value) => value,
+            Err(e) => {
+                eprintln!("{}", e);
+                failed = true;
+                break;
+            }
+        };
+
+        saved.push((&assignment.name, shell.vars.snapshot(&assignment.name)));
+        shell.vars.export(&assignment.name, Some(&value));
+    }
+
+    let status = if failed { 1 } else { run(shell) };
+
+    for (name, snapshot) in saved.into_iter().rev() {
+        shell.vars.restore(name, snapshot);
+    }
+
+    status
+}
@@ -0,0 +1,350 @@
+use std::io;
+
+use thiserror::Error;
+
+use crate::arithmetic::{ArithError, evaluate};
+use crate::ast::{Word, WordPart};
+use crate::brace::expand_braces;
+use crate::glob::glob;
+use crate::parameter::expand_parameter;
+use crate::parser::ParseError;
+use crate::pattern;
+use crate::shell::Shell;
+use crate::substitution::command_substitution;
+use crate::tilde::tilde_prefix;
+
+const DEFAULT_IFS: &str = " \t\n";
+
+#[derive(Debug, Error)]
+pub enum ExpansionError {
+    #[error("{0}: {1}")]
+    Unset(String, String),
+    #[error("${0}: cannot assign in this way")]
+    BadAssignment(String),
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Arith(#[from] ArithError),
+    #[error("{0}: {1}")]
+    Redirect(String, String),
+    #[error("{0}: Bad file descriptor")]
+    BadFd(String),
+    #[error("no match: {0}")]
+    NoMatch(String),
+    #[error("{0}")]
+    Io(#[from] io::Error),
+}
+
+#[derive(Default)]
+struct Field {
+    text: String,
+    pattern: String,
+    has_glob: bool,
+}
+
+struct Fields {
+    fields: Vec<Field>,
+    current: Field,
+    has_current: bool,
+    ifs: String,
+    noglob: bool,
+    extglob: bool,
+}
+
+impl Fields {
+    fn new(shell: &Shell) -> Self {
+        Fields {
+            fields: Vec::new(),
+            current: Field::default(),
+            has_current: false,
+            ifs: shell.vars.get("IFS").unwrap_or(DEFAULT_IFS).to_string(),
+            noglob: shell.options.noglob,
+            extglob: shell.options.extglob,
+        }
+    }
+
+    fn push(&mut self, text: &str, quoted: bool) {
+        self.current.text.push_str(text);
+
+        if quoted {
+            self.current.pattern.push_str(&pattern::escape(text));
+        } else {
+            self.current.pattern.push_str(text);
+            self.current.has_glob |= !self.noglob && pattern::has_meta(text, self.extglob);
+        }
+
+        self.has_current = true;
+    }
+
+    fn finish(&mut self) {
+        self.fields.push(std::mem::take(&mut self.current));
+        self.has_current = false;
+    }
+
+    fn push_split(&mut self, value: &str) {
+        let mut after_whitespace = false;
+
+        for c in value.chars() {
+            if !self.ifs.contains(c) {
+                self.push(c.encode_utf8(&mut [0; 4]), false);
+                after_whitespace = false;
+            } else if c.is_whitespace() {
+                if self.has_current {
+                    self.finish();
+                    after_whitespace = true;
+                }
+            } else if after_whitespace {
+                after_whitespace = false;
+            } else {
+                self.finish();
+            }
+        }
+    }
+
+    fn push_value(&mut self, value: &str, quoted: bool) {
+        if quoted {
+            self.push(value, true);
+        } else {
+            self.push_split(value);
+        }
+    }
+
+    fn into_fields(mut self) -> Vec<Field> {
+        if self.has_current {
+            self.finish();
+        }
+
+        self.fields
+    }
+}
+
+pub fn expand_words(shell: &mut Shell, words: &[Word]) -> Result<Vec<String>, ExpansionError> {
+    let mut args = Vec::new();
+
+    for word in words.iter().flat_map(expand_braces) {
+        for field in expand_fields(shell, &word)? {
+            if !field.has_glob {
+                args.push(field.text);
+                continue;
+            }
+
+            let matches = glob(&field.pattern, &shell.options);
+
+            if !matches.is_empty() {
+                args.extend(matches);
+            } else if shell.options.failglob {
+                return Err(ExpansionError::NoMatch(field.text));
+            } else if !shell.options.nullglob {
+                args.push(field.text);
+            }
+        }
+    }
+
+    Ok(args)
+}
+
+fn expand_fields(shell: &mut Shell, word: &Word) -> Result<Vec<Field>, ExpansionError> {
+    let mut fields = Fields::new(shell);
+    let mut parts = word.parts.iter();
+    let mut only_empty_lists = true;
+    let mut has_empty_list = false;
+
+    if let Some((home, rest)) = tilde_prefix(shell, word) {
+        only_empty_lists = false;
+        fields.push(&home, true);
+        fields.push(&rest, false);
+        parts.next();
+    }
+
+    for part in parts {
+        only_empty_lists &= match part {
+            WordPart::Quoted(text) => text.is_empty(),
+            WordPart::Param { quoted: true, .. } => true,
+            _ => false,
+        };
+
+        match part {
+            WordPart::Literal(text) => fields.push(text, false),
+            WordPart::Quoted(text) => fields.push(text, true),
+            WordPart::Param { param, quoted } => {
+                let values = expand_parameter(shell, param)?;
+                let separate = param.name == "@" || matches!(param.index.as_deref(), Some("@"));
+
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        if *quoted && separate {
+                            fields.finish();
+                        } else if *quoted {
+                            let separator = fields.ifs.chars().next().map(String::from);
+                            fields.push(&separator.unwrap_or_default(), true);
+                        } else if fields.has_current {
+                            fields.finish();
+                        }
+                    }
+
+                    fields.push_value(value, *quoted);
+                }
+
+                if *quoted && values.is_empty() && !separate {
+                    fields.push("", true);
+                }
+
+                if *quoted && separate && values.is_empty() {
+                    has_empty_list = true;
+                } else {
+                    only_empty_lists = false;
+                }
+            }
+            WordPart::CommandSub { body, quoted } => {
+                let output = command_substitution(shell, body)?;
+                fields.push_value(&output, *quoted);
+            }
+            WordPart::Arith { expr, quoted } => {
+                let value = arithmetic_expansion(shell, expr)?;
+                fields.push_value(&value, *quoted);
+            }
+        }
+    }
+
+    if only_empty_lists && has_empty_list {
+        return Ok(Vec::new());
+    }
+
+    Ok(fields.into_fields())
+}
+
+pub fn expand_word(shell: &mut Shell, word: &Word) -> Result<String, ExpansionError> {
+    let mut s = String::new();
+    let mut parts = word.parts.iter();
+
+    if let Some((home, rest)) = tilde_prefix(shell, word) {
+        s.push_str(&home);
+        s.push_str(&rest);
+        parts.next();
+    }
+
+    for part in parts {
+        match part {
+            WordPart::Literal(text) | WordPart::Quoted(text) => s.push_str(text),
+            WordPart::Param { param, .. } => s.push_str(&expand_parameter(shell, param)?.join(" ")),
+            WordPart::CommandSub { body, .. } => s.push_str(&command_substitution(shell, body)?),
+            WordPart::Arith { expr, .. } => s.push_str(&arithmetic_expansion(shell, expr)?),
+        }
+    }
+
+    Ok(s)
+}
+
+pub fn expand_pattern(shell: &mut Shell, word: &Word) -> Result<String, ExpansionError> {
+    let mut s = String::new();
+
+    for part in &word.parts {
+        match part {
+            WordPart::Literal(text) => s.push_str(text),
+            WordPart::Quoted(text) => s.push_str(&pattern::escape(text)),
+            WordPart::Param { param, quoted } => {
+                let value = expand_parameter(shell, param)?.join(" ");
+
+                if *quoted {
+                    s.push_str(&pattern::escape(&value));
+                } else {
+                    s.push_str(&value);
+                }
+            }
+            WordPart::CommandSub { body, quoted } => {
+                let output = command_substitution(shell, body)?;
+
+                if *quoted {
+                    s.push_str(&pattern::escape(&output));
+                } else {
+                    s.push_str(&output);
+                }
+            }
+            WordPart::Arith { expr, .. } => s.push_str(&arithmetic_expansion(shell, expr)?),
+        }
+    }
+
+    Ok(s)
+}
+
+pub fn arithmetic_expansion(shell: &mut Shell, expr: &Word) -> Result<String, ExpansionError> {
+    let expr = expand_word(shell, expr)?;
+    Ok(evaluate(shell, &expr)?.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lexer::{Token, tokenize};
+
+    fn words(source: &str) -> Vec<Word> {
+        tokenize(source)
+            .unwrap()
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Word(word) => Some(word),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn expand(source: &str, value: &str, ifs: Option<&str>) -> Vec<String> {
+        let mut shell = Shell::default();
+        shell.vars.set("x", value);
+
+        if let Some(ifs) = ifs {
+            shell.vars.set("IFS", ifs);
+        }
+
+        expand_words(&mut shell, &words(source)).unwrap()
+    }
+
+    #[test]
+    fn splits_unquoted_expansions_on_default_ifs() {
+        assert_eq!(expand("$x", "  a  b\tc \n", None), ["a", "b", "c"]);
+        assert_eq!(expand("[$x]", "a b", None), ["[a", "b]"]);
+        assert_eq!(expand("$x", "", None), Vec::<String>::new());
+        assert_eq!(expand("$x $x", " ", None), Vec::<String>::new());
+    }
+
+    #[test]
+    fn keeps_quoted_expansions_whole() {
+        assert_eq!(expand("\"$x\"", "  a  b ", None), ["  a  b "]);
+        assert_eq!(expand("\"$x\"", "", None), [""]);
+        assert_eq!(expand("'$x'", "a b", None), ["$x"]);
+    }
+
+    #[test]
+    fn splits_on_custom_ifs() {
+        assert_eq!(expand("$x", "a::b:", Some(":")), ["a", "", "b"]);
+        assert_eq!(expand("$x", " a : b ", Some(" :")), ["a", "b"]);
+        assert_eq!(expand("$x", "a b", Some("")), ["a b"]);
+        assert_eq!(expand("$x", "a b,c", Some(",")), ["a b", "c"]);
+    }
+
+    #[test]
+    fn unmatched_patterns_follow_glob_options() {
+        let words = words("/nonexistent/*.x '*'");
+        let mut shell = Shell::default();
+
+        assert_eq!(
+            expand_words(&mut shell, &words).unwrap(),
+            ["/nonexistent/*.x", "*"]
+        );
+
+        shell.options.nullglob = true;
+        assert_eq!(expand_words(&mut shell, &words).unwrap(), ["*"]);
+
+        shell.options.failglob = true;
+        assert!(matches!(
+            expand_words(&mut shell, &words),
+            Err(ExpansionError::NoMatch(pattern)) if pattern == "/nonexistent/*.x"
+        ));
+
+        shell.options.noglob = true;
+        assert_eq!(
+            expand_words(&mut shell, &words).unwrap(),
+            ["/nonexistent/*.x", "*"]
+        );
+    }
+}
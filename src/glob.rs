@@ -0,0 +1,217 @@
+use std::fs;
+use std::path::Path;
+
+use crate::options::Options;
+use crate::pattern::{Pattern, has_meta, unescape};
+
+pub fn glob(pattern: &str, options: &Options) -> Vec<String> {
+    let (mut paths, rest) = match pattern.strip_prefix('/') {
+        Some(rest) => (vec!["/".to_string()], rest),
+        None => (vec![String::new()], pattern),
+    };
+
+    let components: Vec<&str> = rest.split('/').collect();
+
+    for (i, component) in components.iter().enumerate() {
+        let is_last = i == components.len() - 1;
+
+        if component.is_empty() {
+            if !is_last {
+                continue;
+            }
+
+            paths = paths
+                .into_iter()
+                .filter(|path| Path::new(path).is_dir())
+                .map(|path| join(&path, ""))
+                .collect();
+            break;
+        }
+
+        if *component == "**" && options.globstar {
+            let mut expanded = Vec::new();
+
+            for path in &paths {
+                if !is_last {
+                    expanded.push(path.clone());
+                } else if !path.is_empty() {
+                    expanded.push(join(path, ""));
+                }
+
+                walk(path, options.dotglob, is_last, &mut expanded);
+            }
+
+            paths = expanded;
+            continue;
+        }
+
+        if !has_meta(component, options.extglob) {
+            let name = unescape(component);
+
+            paths = paths
+                .into_iter()
+                .map(|path| join(&path, &name))
+                .filter(|path| is_last || Path::new(path).is_dir())
+                .collect();
+            continue;
+        }
+
+        let pattern = Pattern::new(component, options.extglob);
+        let mut matched = Vec::new();
+
+        for path in &paths {
+            for name in read_names(path) {
+                if name.starts_with('.') && !options.dotglob && !pattern.matches_hidden() {
+                    continue;
+                }
+
+                if pattern.matches(&name) {
+                    matched.push(join(path, &name));
+                }
+            }
+        }
+
+        paths = matched;
+    }
+
+    let mut paths: Vec<String> = paths
+        .into_iter()
+        .filter(|path| fs::symlink_metadata(path.trim_end_matches('/')).is_ok() || path == "/")
+        .collect();
+
+    paths.sort();
+    paths
+}
+
+fn walk(dir: &str, dotglob: bool, include_files: bool, paths: &mut Vec<String>) {
+    for name in read_names(dir) {
+        if name.starts_with('.') && !dotglob {
+            continue;
+        }
+
+        let path = join(dir, &name);
+
+        let is_dir = fs::symlink_metadata(&path).is_ok_and(|metadata| metadata.is_dir());
+
+        if is_dir || include_files {
+            paths.push(path.clone());
+        }
+
+        if is_dir {
+            walk(&path, dotglob, include_files, paths);
+        }
+    }
+}
+
+fn read_names(dir: &str) -> Vec<String> {
+    let dir = if dir.is_empty() { "." } else { dir };
+
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .collect()
+}
+
+fn join(dir: &str, name: &str) -> String {
+    if dir.is_empty() {
+        name.to_string()
+    } else if dir.ends_with('/') {
+        format!("{}{}", dir, name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(name: &str, files: &[&str]) -> String {
+        let root = std::env::temp_dir().join(format!("glob-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+
+        for file in files {
+            let path = root.join(file);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, "").unwrap();
+        }
+
+        root.to_str().unwrap().to_string()
+    }
+
+    fn names(root: &str, pattern: &str) -> Vec<String> {
+        names_with(root, pattern, &Options::default())
+    }
+
+    fn names_with(root: &str, pattern: &str, options: &Options) -> Vec<String> {
+        glob(&format!("{}/{}", root, pattern), options)
+            .into_iter()
+            .map(|path| path[root.len() + 1..].to_string())
+            .collect()
+    }
+
+    #[test]
+    fn skips_hidden_files_unless_asked() {
+        let root = tree("hidden", &["a.rs", "b.txt", ".c.rs"]);
+
+        assert_eq!(names(&root, "*"), ["a.rs", "b.txt"]);
+        assert_eq!(names(&root, "*.rs"), ["a.rs"]);
+        assert_eq!(names(&root, ".*.rs"), [".c.rs"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn walks_directories_component_by_component() {
+        let root = tree("nested", &["x/1.rs", "x/2.txt", "y/3.rs", "z"]);
+
+        assert_eq!(names(&root, "*/*.rs"), ["x/1.rs", "y/3.rs"]);
+        assert_eq!(names(&root, "*/"), ["x/", "y/"]);
+        assert_eq!(names(&root, "x/[12].*"), ["x/1.rs", "x/2.txt"]);
+        assert!(names(&root, "w*").is_empty());
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn dotglob_includes_hidden_files() {
+        let root = tree("dotglob", &["a", ".b"]);
+        let options = Options {
+            dotglob: true,
+            ..Options::default()
+        };
+
+        assert_eq!(names_with(&root, "*", &options), [".b", "a"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn globstar_matches_any_depth() {
+        let root = tree(
+            "globstar",
+            &["a.rs", "x/b.rs", "x/y/c.rs", "x/y/d.txt", ".h/e.rs"],
+        );
+        let options = Options {
+            globstar: true,
+            ..Options::default()
+        };
+
+        assert_eq!(
+            names_with(&root, "**/*.rs", &options),
+            ["a.rs", "x/b.rs", "x/y/c.rs"]
+        );
+        assert_eq!(names_with(&root, "**/", &options), ["", "x/", "x/y/"]);
+        assert_eq!(
+            names_with(&root, "x/**", &options),
+            ["x/", "x/b.rs", "x/y", "x/y/c.rs", "x/y/d.txt"]
+        );
+        assert_eq!(names(&root, "**/*.rs"), ["x/b.rs"]);
+
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,495 @@
+use std::io;
+use std::mem::MaybeUninit;
+use std::sync::atomic::Ordering;
+
+use crate::process::status_code;
+use crate::signals::{CHILD_CHANGED, describe_signal};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobState {
+    Running,
+    Stopped,
+    Done(i32),
+}
+
+pub struct Process {
+    pub pid: libc::pid_t,
+    pub status: Option<i32>,
+    pub stopped: Option<i32>,
+    pub signal: Option<(i32, bool)>,
+}
+
+pub struct Job {
+    pub id: usize,
+    pub pgid: libc::pid_t,
+    pub processes: Vec<Process>,
+    pub command: String,
+    pub modes: Option<libc::termios>,
+}
+
+impl Job {
+    pub fn new(pgid: libc::pid_t, pids: &[libc::pid_t], command: &str) -> Self {
+        Job {
+            id: 0,
+            pgid,
+            processes: pids
+                .iter()
+                .map(|&pid| Process {
+                    pid,
+                    status: None,
+                    stopped: None,
+                    signal: None,
+                })
+                .collect(),
+            command: command.to_string(),
+            modes: None,
+        }
+    }
+
+    fn signal(&self) -> Option<(i32, bool)> {
+        self.processes
+            .iter()
+            .filter_map(|process| process.signal)
+            .find(|&(signal, _)| signal != libc::SIGPIPE)
+    }
+
+    fn stop_signal(&self) -> Option<i32> {
+        self.processes.iter().find_map(|process| process.stopped)
+    }
+
+    pub fn state(&self) -> JobState {
+        if self
+            .processes
+            .iter()
+            .all(|process| process.status.is_some())
+        {
+            JobState::Done(self.processes.last().and_then(|p| p.status).unwrap_or(0))
+        } else if self
+            .processes
+            .iter()
+            .any(|process| process.stopped.is_some())
+        {
+            JobState::Stopped
+        } else {
+            JobState::Running
+        }
+    }
+
+    fn wait(&mut self, options: i32) {
+        for process in &mut self.processes {
+            if process.status.is_some() {
+                continue;
+            }
+
+            let mut status = 0;
+
+            let result = loop {
+                match unsafe { libc::waitpid(process.pid, &mut status, options) } {
+                    -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {}
+                    result => break result,
+                }
+            };
+
+            if result == -1 {
+                process.status = Some(process.status.unwrap_or(1));
+                process.stopped = None;
+            } else if result != process.pid {
+                continue;
+            } else if libc::WIFSTOPPED(status) {
+                process.stopped = Some(libc::WSTOPSIG(status));
+            } else if libc::WIFCONTINUED(status) {
+                process.stopped = None;
+            } else {
+                process.status = Some(status_code(status));
+                process.stopped = None;
+
+                if libc::WIFSIGNALED(status) {
+                    process.signal = Some((libc::WTERMSIG(status), libc::WCOREDUMP(status)));
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Jobs {
+    jobs: Vec<Job>,
+    current: Option<usize>,
+    previous: Option<usize>,
+    pub job_control: bool,
+    shell_pgid: libc::pid_t,
+    shell_modes: Option<libc::termios>,
+}
+
+impl Jobs {
+    pub fn enable_job_control(&mut self) {
+        if unsafe { libc::isatty(0) } != 1 {
+            return;
+        }
+
+        unsafe {
+            libc::setpgid(0, 0);
+            self.shell_pgid = libc::getpgrp();
+            libc::tcsetpgrp(0, self.shell_pgid);
+        }
+
+        self.shell_modes = terminal_modes();
+        self.job_control = true;
+    }
+
+    pub fn add(&mut self, mut job: Job) -> usize {
+        if job.id == 0 {
+            job.id = self.jobs.last().map_or(1, |job| job.id + 1);
+        }
+
+        let id = job.id;
+        let index = self.jobs.partition_point(|existing| existing.id < id);
+        self.jobs.insert(index, job);
+
+        if self.current != Some(id) {
+            self.previous = self.current;
+            self.current = Some(id);
+        }
+
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Job> {
+        self.jobs.iter().find(|job| job.id == id)
+    }
+
+    pub fn get_mut(&mut self, id: usize) -> Option<&mut Job> {
+        self.jobs.iter_mut().find(|job| job.id == id)
+    }
+
+    pub fn ids(&self) -> Vec<usize> {
+        self.jobs.iter().map(|job| job.id).collect()
+    }
+
+    pub fn current(&self) -> Option<usize> {
+        self.current
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Job> {
+        let index = self.jobs.iter().position(|job| job.id == id)?;
+        let job = self.jobs.remove(index);
+
+        if self.current == Some(id) {
+            self.current = self.previous.take();
+        } else if self.previous == Some(id) {
+            self.previous = None;
+        }
+
+        if self.previous.is_none() {
+            self.previous = self
+                .jobs
+                .iter()
+                .rev()
+                .map(|job| job.id)
+                .find(|&id| Some(id) != self.current);
+        }
+
+        if self.current.is_none() {
+            self.current = self.previous.take();
+        }
+
+        Some(job)
+    }
+
+    pub fn find_pid(&self, pid: libc::pid_t) -> Option<usize> {
+        self.jobs
+            .iter()
+            .find(|job| job.processes.iter().any(|process| process.pid == pid))
+            .map(|job| job.id)
+    }
+
+    pub fn update(&mut self) {
+        if !CHILD_CHANGED.swap(false, Ordering::SeqCst) {
+            return;
+        }
+
+        let options = libc::WNOHANG | libc::WUNTRACED | libc::WCONTINUED;
+
+        for job in &mut self.jobs {
+            job.wait(options);
+        }
+    }
+
+    pub fn notify(&mut self) {
+        self.update();
+
+        let done: Vec<usize> = self
+            .jobs
+            .iter()
+            .filter(|job| matches!(job.state(), JobState::Done(_)))
+            .map(|job| job.id)
+            .collect();
+
+        for id in done {
+            if let Some(job) = self.get(id) {
+                println!("{}", self.describe(job, false));
+            }
+            self.remove(id);
+        }
+    }
+
+    pub fn signal(&self, id: usize, signal: i32) -> io::Result<()> {
+        let Some(job) = self.get(id) else {
+            return Ok(());
+        };
+
+        let target = if self.job_control {
+            -job.pgid
+        } else {
+            job.processes[0].pid
+        };
+
+        match unsafe { libc::kill(target, signal) } {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn continue_job(&mut self, id: usize) -> io::Result<()> {
+        self.signal(id, libc::SIGCONT)?;
+
+        if let Some(job) = self.get_mut(id) {
+            for process in &mut job.processes {
+                process.stopped = None;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn foreground(&mut self, mut job: Job) -> Vec<i32> {
+        if self.job_control {
+            unsafe { libc::tcsetpgrp(0, job.pgid) };
+
+            if let Some(modes) = &job.modes {
+                unsafe { libc::tcsetattr(0, libc::TCSADRAIN, modes) };
+            }
+        }
+
+        job.wait(if self.job_control { libc::WUNTRACED } else { 0 });
+
+        let stopped = job.state() == JobState::Stopped;
+
+        let signal = job.signal();
+
+        if self.job_control {
+            unsafe { libc::tcsetpgrp(0, self.shell_pgid) };
+
+            if stopped {
+                job.modes = terminal_modes();
+            }
+
+            if (stopped || signal.is_some())
+                && let Some(modes) = &self.shell_modes
+            {
+                unsafe { libc::tcsetattr(0, libc::TCSADRAIN, modes) };
+            }
+        }
+
+        let stop_signal = job.stop_signal().unwrap_or(0);
+
+        let statuses = job
+            .processes
+            .iter()
+            .map(|process| match process.status {
+                Some(status) => status,
+                None => 128 + process.stopped.unwrap_or(stop_signal),
+            })
+            .collect();
+
+        match signal {
+            Some((libc::SIGINT, _)) => println!(),
+            Some((signal, core_dumped)) if !stopped => {
+                eprintln!("{}", describe_signal(signal, core_dumped));
+            }
+            _ => {}
+        }
+
+        if stopped {
+            let id = self.add(job);
+
+            if let Some(job) = self.get(id) {
+                println!();
+                println!("{}", self.describe(job, false));
+            }
+        }
+
+        statuses
+    }
+
+    pub fn wait(&mut self, id: usize) -> Option<i32> {
+        let job = self.get_mut(id)?;
+        job.wait(libc::WNOHANG | libc::WUNTRACED | libc::WCONTINUED);
+
+        if job.state() != JobState::Stopped {
+            job.wait(libc::WUNTRACED);
+        }
+
+        if let JobState::Done(status) = job.state() {
+            self.remove(id);
+            return Some(status);
+        }
+
+        let status = 128 + job.stop_signal().unwrap_or(0);
+
+        if let Some(job) = self.get(id) {
+            println!("{}", self.describe(job, false));
+        }
+
+        Some(status)
+    }
+
+    pub fn describe(&self, job: &Job, show_pid: bool) -> String {
+        let marker = self.marker(job.id);
+
+        let state = match (job.state(), job.signal()) {
+            (JobState::Running, _) => "Running".to_string(),
+            (JobState::Stopped, _) => "Stopped".to_string(),
+            (JobState::Done(_), Some((signal, core_dumped))) => {
+                describe_signal(signal, core_dumped)
+            }
+            (JobState::Done(0), None) => "Done".to_string(),
+            (JobState::Done(code), None) => format!("Exit {}", code),
+        };
+
+        let suffix = if job.state() == JobState::Running {
+            " &"
+        } else {
+            ""
+        };
+
+        if show_pid {
+            format!(
+                "[{}]{} {} {:<24}{}{}",
+                job.id, marker, job.processes[0].pid, state, job.command, suffix
+            )
+        } else {
+            format!(
+                "[{}]{}  {:<24}{}{}",
+                job.id, marker, state, job.command, suffix
+            )
+        }
+    }
+
+    pub fn marker(&self, id: usize) -> char {
+        if self.current == Some(id) {
+            '+'
+        } else if self.previous == Some(id) {
+            '-'
+        } else {
+            ' '
+        }
+    }
+
+    pub fn find(&self, spec: &str) -> Result<usize, String> {
+        let no_such_job = || format!("{}: no such job", spec);
+
+        let Some(body) = spec.strip_prefix('%') else {
+            return Err(no_such_job());
+        };
+
+        let candidates: Vec<usize> = match body {
+            "" | "%" | "+" => self.current.into_iter().collect(),
+            "-" => self.previous.into_iter().collect(),
+            number if number.chars().all(|c| c.is_ascii_digit()) => {
+                let id = number.parse().map_err(|_| no_such_job())?;
+                self.get(id).map(|job| job.id).into_iter().collect()
+            }
+            _ => {
+                let matches = |job: &&Job| match body.strip_prefix('?') {
+                    Some(text) => job.command.contains(text),
+                    None => job.command.starts_with(body),
+                };
+
+                self.jobs.iter().filter(matches).map(|job| job.id).collect()
+            }
+        };
+
+        match candidates[..] {
+            [id] => Ok(id),
+            [] => Err(no_such_job()),
+            _ => Err(format!("{}: ambiguous job spec", spec)),
+        }
+    }
+}
+
+fn terminal_modes() -> Option<libc::termios> {
+    let mut modes = MaybeUninit::uninit();
+
+    match unsafe { libc::tcgetattr(0, modes.as_mut_ptr()) } {
+        0 => Some(unsafe { modes.assume_init() }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn jobs() -> Jobs {
+        let mut jobs = Jobs::default();
+        jobs.add(Job::new(-101, &[-101], "sleep 10"));
+        jobs.add(Job::new(-102, &[-102], "vim notes"));
+        jobs.add(Job::new(-103, &[-103], "sleep 20"));
+        jobs
+    }
+
+    #[test]
+    fn finds_jobs_by_number_and_marker() {
+        let jobs = jobs();
+
+        assert_eq!(jobs.find("%1"), Ok(1));
+        assert_eq!(jobs.find("%+"), Ok(3));
+        assert_eq!(jobs.find("%%"), Ok(3));
+        assert_eq!(jobs.find("%"), Ok(3));
+        assert_eq!(jobs.find("%-"), Ok(2));
+        assert_eq!(jobs.find("%4"), Err("%4: no such job".to_string()));
+        assert_eq!(jobs.find("1"), Err("1: no such job".to_string()));
+    }
+
+    #[test]
+    fn finds_jobs_by_command_text() {
+        let jobs = jobs();
+
+        assert_eq!(jobs.find("%vim"), Ok(2));
+        assert_eq!(jobs.find("%?notes"), Ok(2));
+        assert_eq!(jobs.find("%?20"), Ok(3));
+        assert_eq!(
+            jobs.find("%sleep"),
+            Err("%sleep: ambiguous job spec".to_string())
+        );
+        assert_eq!(jobs.find("%emacs"), Err("%emacs: no such job".to_string()));
+    }
+
+    #[test]
+    fn moves_markers_when_jobs_are_removed() {
+        let mut jobs = jobs();
+
+        jobs.remove(3);
+        assert_eq!((jobs.find("%+"), jobs.find("%-")), (Ok(2), Ok(1)));
+
+        jobs.remove(1);
+        assert_eq!(jobs.find("%+"), Ok(2));
+        assert!(jobs.find("%-").is_err());
+        assert_eq!(jobs.add(Job::new(-104, &[-104], "make")), 3);
+    }
+
+    #[test]
+    fn derives_state_from_all_processes() {
+        let mut job = Job::new(-105, &[-105, -106], "a | b");
+        assert_eq!(job.state(), JobState::Running);
+
+        job.processes[1].stopped = Some(libc::SIGSTOP);
+        assert_eq!(job.state(), JobState::Stopped);
+
+        job.processes[0].status = Some(1);
+        job.processes[1].status = Some(0);
+        job.processes[1].stopped = None;
+        assert_eq!(job.state(), JobState::Done(0));
+    }
+}
@@ -0,0 +1,1110 @@
+use crate::ast::{ParamOp, Parameter, RedirectOp, ReplaceMode, Word, WordPart};
+use crate::parser::ParseError;
+use crate::variables::is_valid_name;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operator {
+    Pipe,
+    PipeBoth,
+    Background,
+    Semi,
+    DoubleSemi,
+    SemiAnd,
+    DoubleSemiAnd,
+    And,
+    Or,
+    OpenParen,
+    CloseParen,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Token {
+    Word(Word),
+    Arith(Word),
+    Operator(Operator),
+    Redirect { fd: Option<u32>, op: RedirectOp },
+    Newline,
+}
+
+struct HereDoc {
+    index: usize,
+    delimiter: String,
+    quoted: bool,
+    strip_tabs: bool,
+}
+
+struct Lexer {
+    chars: Vec<char>,
+    pos: usize,
+    tokens: Vec<Token>,
+    heredocs: Vec<HereDoc>,
+}
+
+pub fn tokenize(input: &str) -> Result<Vec<Token>, ParseError> {
+    let mut lexer = Lexer::new(input);
+
+    lexer.run()?;
+
+    Ok(lexer.tokens)
+}
+
+impl Lexer {
+    fn new(text: &str) -> Self {
+        Lexer {
+            chars: text.chars().collect(),
+            pos: 0,
+            tokens: Vec::new(),
+            heredocs: Vec::new(),
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn peek_at(&self, offset: usize) -> Option<char> {
+        self.chars.get(self.pos + offset).copied()
+    }
+
+    fn run(&mut self) -> Result<(), ParseError> {
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' => self.pos += 1,
+                '#' => {
+                    while self.peek().is_some_and(|c| c != '\n') {
+                        self.pos += 1;
+                    }
+                }
+                '\n' => {
+                    self.pos += 1;
+                    self.tokens.push(Token::Newline);
+                    self.heredoc_bodies()?;
+                }
+                '|' if self.peek_at(1) == Some('|') => self.operator(Operator::Or, 2),
+                '|' if self.peek_at(1) == Some('&') => self.operator(Operator::PipeBoth, 2),
+                '|' => self.operator(Operator::Pipe, 1),
+                '&' if self.peek_at(1) == Some('&') => self.operator(Operator::And, 2),
+                '&' if self.peek_at(1) == Some('>') => self.redirect(None)?,
+                '&' => self.operator(Operator::Background, 1),
+                ';' if self.peek_at(1) == Some(';') && self.peek_at(2) == Some('&') => {
+                    self.operator(Operator::DoubleSemiAnd, 3)
+                }
+                ';' if self.peek_at(1) == Some(';') => self.operator(Operator::DoubleSemi, 2),
+                ';' if self.peek_at(1) == Some('&') => self.operator(Operator::SemiAnd, 2),
+                ';' => self.operator(Operator::Semi, 1),
+                '>' | '<' => self.redirect(None)?,
+                '(' if self.peek_at(1) == Some('(') => {
+                    let start = self.pos;
+                    self.pos += 2;
+
+                    match self.arith_body()? {
+                        Some(expr) => self.tokens.push(Token::Arith(lex_word(&expr, true)?)),
+                        None => {
+                            self.pos = start;
+                            self.operator(Operator::OpenParen, 1);
+                        }
+                    }
+                }
+                '(' => self.operator(Operator::OpenParen, 1),
+                ')' => self.operator(Operator::CloseParen, 1),
+                '\\' if self.peek_at(1) == Some('\n') => self.pos += 2,
+                _ => {
+                    if let Some(fd) = self.io_number() {
+                        self.redirect(Some(fd))?;
+                    } else {
+                        let word = self.word()?;
+                        self.tokens.push(Token::Word(word));
+                    }
+                }
+            }
+        }
+
+        match self.heredocs.first() {
+            Some(heredoc) => Err(ParseError::HereDoc(heredoc.delimiter.clone())),
+            None => Ok(()),
+        }
+    }
+
+    fn operator(&mut self, operator: Operator, len: usize) {
+        self.pos += len;
+        self.tokens.push(Token::Operator(operator));
+    }
+
+    fn io_number(&mut self) -> Option<u32> {
+        let mut end = self.pos;
+
+        while end < self.chars.len() && self.chars[end].is_ascii_digit() {
+            end += 1;
+        }
+
+        if end == self.pos || !matches!(self.chars.get(end), Some('>' | '<')) {
+            return None;
+        }
+
+        let digits: String = self.chars[self.pos..end].iter().collect();
+        let fd = digits.parse().ok()?;
+
+        self.pos = end;
+        Some(fd)
+    }
+
+    fn redirect(&mut self, fd: Option<u32>) -> Result<(), ParseError> {
+        let (op, len) = match (self.peek(), self.peek_at(1), self.peek_at(2)) {
+            (Some('&'), _, Some('>')) => (RedirectOp::AppendBoth, 3),
+            (Some('&'), _, _) => (RedirectOp::WriteBoth, 2),
+            (Some('>'), Some('>'), _) => (RedirectOp::Append, 2),
+            (Some('>'), Some('&'), _) => (RedirectOp::DupOutput, 2),
+            (Some('>'), Some('|'), _) => (RedirectOp::Clobber, 2),
+            (Some('>'), _, _) => (RedirectOp::Write, 1),
+            (_, Some('<'), Some('<')) => (RedirectOp::HereString, 3),
+            (_, Some('<'), _) => {
+                self.pos += 2;
+                return self.heredoc(fd);
+            }
+            (_, Some('&'), _) => (RedirectOp::DupInput, 2),
+            (_, Some('>'), _) => (RedirectOp::ReadWrite, 2),
+            _ => (RedirectOp::Read, 1),
+        };
+
+        self.pos += len;
+
+        self.tokens.push(Token::Redirect { fd, op });
+        Ok(())
+    }
+
+    fn heredoc(&mut self, fd: Option<u32>) -> Result<(), ParseError> {
+        let strip_tabs = self.peek() == Some('-');
+
+        if strip_tabs {
+            self.pos += 1;
+        }
+
+        while matches!(self.peek(), Some(' ' | '\t')) {
+            self.pos += 1;
+        }
+
+        let word = self.word()?;
+
+        if word.parts.is_empty() {
+            return Err(ParseError::Unexpected(
+                self.peek().map_or("newline".to_string(), String::from),
+            ));
+        }
+
+        let quoted = word
+            .parts
+            .iter()
+            .any(|part| !matches!(part, WordPart::Literal(_)));
+
+        let delimiter = word
+            .parts
+            .iter()
+            .map(|part| match part {
+                WordPart::Literal(text) | WordPart::Quoted(text) => text.clone(),
+                part => Word {
+                    parts: vec![part.clone()],
+                }
+                .expression(),
+            })
+            .collect();
+
+        self.tokens.push(Token::Redirect {
+            fd,
+            op: RedirectOp::HereDoc,
+        });
+
+        self.heredocs.push(HereDoc {
+            index: self.tokens.len(),
+            delimiter,
+            quoted,
+            strip_tabs,
+        });
+
+        self.tokens.push(Token::Word(Word::default()));
+        Ok(())
+    }
+
+    fn heredoc_bodies(&mut self) -> Result<(), ParseError> {
+        for heredoc in std::mem::take(&mut self.heredocs) {
+            let mut body = String::new();
+
+            loop {
+                if self.pos >= self.chars.len() {
+                    return Err(ParseError::HereDoc(heredoc.delimiter));
+                }
+
+                let end = self.chars[self.pos..]
+                    .iter()
+                    .position(|&c| c == '\n')
+                    .map_or(self.chars.len(), |offset| self.pos + offset);
+
+                let mut line: String = self.chars[self.pos..end].iter().collect();
+                self.pos = (end + 1).min(self.chars.len());
+
+                if heredoc.strip_tabs {
+                    line = line.trim_start_matches('\t').to_string();
+                }
+
+                if line == heredoc.delimiter {
+                    break;
+                }
+
+                body.push_str(&line);
+                body.push('\n');
+            }
+
+            let word = if heredoc.quoted {
+                let mut word = Word::default();
+                word.push_quoted(&body);
+                word
+            } else {
+                heredoc_word(&body)?
+            };
+
+            self.tokens[heredoc.index] = Token::Word(word);
+        }
+
+        Ok(())
+    }
+
+    fn heredoc_text(&mut self, word: &mut Word) -> Result<(), ParseError> {
+        word.push_quoted("");
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+
+            match c {
+                '\\' => match self.peek() {
+                    Some('\n') => self.pos += 1,
+                    Some(escaped @ ('$' | '`' | '\\')) => {
+                        self.pos += 1;
+                        word.push_quoted(&escaped.to_string());
+                    }
+                    _ => word.push_quoted("\\"),
+                },
+                '$' => {
+                    self.pos -= 1;
+                    self.dollar(word, true)?;
+                }
+                '`' => {
+                    let body = self.backquoted(true)?;
+                    word.parts.push(WordPart::CommandSub { body, quoted: true });
+                }
+                c => word.push_quoted(&c.to_string()),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn word(&mut self) -> Result<Word, ParseError> {
+        self.read_word(true)
+    }
+
+    fn read_word(&mut self, stop_at_separators: bool) -> Result<Word, ParseError> {
+        let mut word = Word::default();
+
+        while let Some(c) = self.peek() {
+            match c {
+                ' ' | '\t' | '\r' | '\n' | '|' | '&' | ';' | '<' | '>' | '(' | ')'
+                    if stop_at_separators =>
+                {
+                    break;
+                }
+                '\'' => {
+                    self.pos += 1;
+                    let text = self.single_quoted()?;
+                    word.push_quoted(&text);
+                }
+                '"' => {
+                    self.pos += 1;
+                    self.double_quoted(&mut word)?;
+                }
+                '$' => self.dollar(&mut word, false)?,
+                '`' => {
+                    self.pos += 1;
+                    let body = self.backquoted(false)?;
+                    word.parts.push(WordPart::CommandSub {
+                        body,
+                        quoted: false,
+                    });
+                }
+                '\\' => {
+                    self.pos += 1;
+
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(escaped) => {
+                            self.pos += 1;
+                            word.push_quoted(&escaped.to_string());
+                        }
+                        None => return Err(ParseError::UnexpectedEof),
+                    }
+                }
+                '?' | '*' | '+' | '@' | '!' if self.peek_at(1) == Some('(') => {
+                    self.extglob_group(&mut word)?;
+                }
+                _ => {
+                    self.pos += 1;
+                    word.push_literal(c);
+                }
+            }
+        }
+
+        Ok(word)
+    }
+
+    fn extglob_group(&mut self, word: &mut Word) -> Result<(), ParseError> {
+        let mut depth = 0;
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+
+            match c {
+                '\\' => {
+                    word.push_literal(c);
+
+                    if let Some(escaped) = self.peek() {
+                        self.pos += 1;
+                        word.push_literal(escaped);
+                    }
+                    continue;
+                }
+                '(' => depth += 1,
+                ')' => depth -= 1,
+                _ => {}
+            }
+
+            word.push_literal(c);
+
+            if depth == 0 && c == ')' {
+                return Ok(());
+            }
+        }
+
+        Err(ParseError::Incomplete(")".to_string()))
+    }
+
+    fn single_quoted(&mut self) -> Result<String, ParseError> {
+        let mut text = String::new();
+
+        loop {
+            match self.peek() {
+                Some('\'') => {
+                    self.pos += 1;
+                    return Ok(text);
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    text.push(c);
+                }
+                None => return Err(ParseError::Incomplete("'".to_string())),
+            }
+        }
+    }
+
+    fn double_quoted(&mut self, word: &mut Word) -> Result<(), ParseError> {
+        word.push_quoted("");
+
+        loop {
+            match self.peek() {
+                Some('"') => {
+                    self.pos += 1;
+                    return Ok(());
+                }
+                Some('\\') => {
+                    self.pos += 1;
+
+                    match self.peek() {
+                        Some('\n') => self.pos += 1,
+                        Some(c @ ('$' | '`' | '"' | '\\')) => {
+                            self.pos += 1;
+                            word.push_quoted(&c.to_string());
+                        }
+                        Some(c) => {
+                            self.pos += 1;
+                            word.push_quoted(&format!("\\{}", c));
+                        }
+                        None => return Err(ParseError::Incomplete("\"".to_string())),
+                    }
+                }
+                Some('$') => self.dollar(word, true)?,
+                Some('`') => {
+                    self.pos += 1;
+                    let body = self.backquoted(true)?;
+                    word.parts.push(WordPart::CommandSub { body, quoted: true });
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    word.push_quoted(&c.to_string());
+                }
+                None => return Err(ParseError::Incomplete("\"".to_string())),
+            }
+        }
+    }
+
+    fn dollar(&mut self, word: &mut Word, quoted: bool) -> Result<(), ParseError> {
+        self.pos += 1;
+
+        let param = match self.peek() {
+            Some('(') if self.peek_at(1) == Some('(') => {
+                let start = self.pos;
+                self.pos += 2;
+
+                match self.arith_body()? {
+                    Some(expr) => {
+                        let expr = lex_word(&expr, true)?;
+                        word.parts.push(WordPart::Arith { expr, quoted });
+                    }
+                    None => {
+                        self.pos = start + 1;
+                        let body = self.command_body()?;
+                        word.parts.push(WordPart::CommandSub { body, quoted });
+                    }
+                }
+
+                return Ok(());
+            }
+            Some('(') => {
+                self.pos += 1;
+                let body = self.command_body()?;
+                word.parts.push(WordPart::CommandSub { body, quoted });
+                return Ok(());
+            }
+            Some('{') => {
+                self.pos += 1;
+                self.braced_parameter(quoted)?
+            }
+            Some(c @ ('?' | '$' | '!' | '#' | '@' | '*' | '0'..='9')) => {
+                self.pos += 1;
+                Parameter {
+                    name: c.to_string(),
+                    index: None,
+                    op: None,
+                }
+            }
+            Some(c) if c.is_ascii_alphabetic() || c == '_' => Parameter {
+                name: self.name(),
+                index: None,
+                op: None,
+            },
+            _ => {
+                if quoted {
+                    word.push_quoted("$");
+                } else {
+                    word.push_literal('$');
+                }
+                return Ok(());
+            }
+        };
+
+        word.parts.push(WordPart::Param { param, quoted });
+        Ok(())
+    }
+
+    fn command_body(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+        let mut depth = 1;
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+
+            match c {
+                '\\' => self.pos += 1,
+                '\'' => {
+                    self.single_quoted()?;
+                }
+                '"' => {
+                    self.double_quoted(&mut Word::default())?;
+                }
+                '`' => {
+                    self.backquoted(false)?;
+                }
+                '(' => depth += 1,
+                ')' => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        return Ok(self.chars[start..self.pos - 1].iter().collect());
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Err(ParseError::Incomplete(")".to_string()))
+    }
+
+    fn arith_body(&mut self) -> Result<Option<String>, ParseError> {
+        let start = self.pos;
+        let mut depth = 0;
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+
+            match c {
+                '(' => depth += 1,
+                ')' if depth > 0 => depth -= 1,
+                ')' if self.peek() == Some(')') => {
+                    self.pos += 1;
+                    return Ok(Some(self.chars[start..self.pos - 2].iter().collect()));
+                }
+                ')' => return Ok(None),
+                _ => {}
+            }
+        }
+
+        Err(ParseError::Incomplete("))".to_string()))
+    }
+
+    fn backquoted(&mut self, quoted: bool) -> Result<String, ParseError> {
+        let mut body = String::new();
+
+        loop {
+            match self.peek() {
+                Some('`') => {
+                    self.pos += 1;
+                    return Ok(body);
+                }
+                Some('\\') => {
+                    self.pos += 1;
+
+                    match self.peek() {
+                        Some(c @ ('$' | '`' | '\\')) => {
+                            self.pos += 1;
+                            body.push(c);
+                        }
+                        Some('"') if quoted => {
+                            self.pos += 1;
+                            body.push('"');
+                        }
+                        Some(c) => {
+                            self.pos += 1;
+                            body.push('\\');
+                            body.push(c);
+                        }
+                        None => return Err(ParseError::Incomplete("`".to_string())),
+                    }
+                }
+                Some(c) => {
+                    self.pos += 1;
+                    body.push(c);
+                }
+                None => return Err(ParseError::Incomplete("`".to_string())),
+            }
+        }
+    }
+
+    fn name(&mut self) -> String {
+        let mut name = String::new();
+
+        while let Some(c) = self.peek() {
+            if c.is_ascii_alphanumeric() || c == '_' {
+                name.push(c);
+                self.pos += 1;
+            } else {
+                break;
+            }
+        }
+
+        name
+    }
+
+    fn braced_parameter(&mut self, quoted: bool) -> Result<Parameter, ParseError> {
+        let start = self.pos;
+        let mut depth = 1;
+
+        while let Some(c) = self.peek() {
+            self.pos += 1;
+
+            match c {
+                '\\' => self.pos += 1,
+                '\'' if !quoted => {
+                    self.single_quoted()?;
+                }
+                '"' => {
+                    self.double_quoted(&mut Word::default())?;
+                }
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+
+                    if depth == 0 {
+                        let body: String = self.chars[start..self.pos - 1].iter().collect();
+                        return parse_parameter(&body, quoted);
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Err(ParseError::Incomplete("}".to_string()))
+    }
+}
+
+fn heredoc_word(body: &str) -> Result<Word, ParseError> {
+    let mut lexer = Lexer::new(body);
+
+    let mut word = Word::default();
+    lexer.heredoc_text(&mut word)?;
+
+    Ok(word)
+}
+
+fn lex_word(text: &str, quoted: bool) -> Result<Word, ParseError> {
+    let mut lexer = Lexer::new(text);
+
+    let mut word = lexer.read_word(false)?;
+
+    if quoted {
+        for part in &mut word.parts {
+            match part {
+                WordPart::Literal(text) => *part = WordPart::Quoted(std::mem::take(text)),
+                WordPart::Param { quoted, .. }
+                | WordPart::CommandSub { quoted, .. }
+                | WordPart::Arith { quoted, .. } => *quoted = true,
+                WordPart::Quoted(_) => {}
+            }
+        }
+    }
+
+    Ok(word)
+}
+
+fn split_unquoted(text: &str, separator: char) -> (&str, Option<&str>) {
+    let mut in_single_quotes = false;
+    let mut in_double_quotes = false;
+    let mut escape_next = false;
+
+    for (i, c) in text.char_indices() {
+        if escape_next {
+            escape_next = false;
+        } else if in_single_quotes {
+            in_single_quotes = c != '\'';
+        } else {
+            match c {
+                '\\' => escape_next = true,
+                '\'' if !in_double_quotes => in_single_quotes = true,
+                '"' => in_double_quotes = !in_double_quotes,
+                c if c == separator && !in_double_quotes => {
+                    return (&text[..i], Some(&text[i + c.len_utf8()..]));
+                }
+                _ => {}
+            }
+        }
+    }
+
+    (text, None)
+}
+
+fn parse_parameter(body: &str, quoted: bool) -> Result<Parameter, ParseError> {
+    let bad_substitution = || ParseError::BadSubstitution(format!("${{{}}}", body));
+
+    if let Some(target) = body.strip_prefix('#')
+        && !target.is_empty()
+    {
+        let mut param = parse_parameter(target, quoted)?;
+
+        if param.op.is_some() {
+            return Err(bad_substitution());
+        }
+
+        param.op = Some(Box::new(ParamOp::Length));
+        return Ok(param);
+    }
+
+    let name_len = match body.chars().next() {
+        Some('?' | '$' | '!' | '#' | '@' | '*') => 1,
+        Some(c) if c.is_ascii_digit() => body
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(body.len()),
+        _ => body
+            .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+            .unwrap_or(body.len()),
+    };
+
+    let name = &body[..name_len];
+
+    let special = matches!(name, "?" | "$" | "!" | "#" | "@" | "*")
+        || name.chars().all(|c| c.is_ascii_digit());
+
+    if !special && !is_valid_name(name) {
+        return Err(bad_substitution());
+    }
+
+    let mut rest = &body[name_len..];
+    let mut index = None;
+
+    if let Some(after_bracket) = rest.strip_prefix('[') {
+        let Some(end) = after_bracket.find(']') else {
+            return Err(bad_substitution());
+        };
+
+        index = Some(after_bracket[..end].to_string());
+        rest = &after_bracket[end + 1..];
+    }
+
+    let (colon, rest) = match rest.strip_prefix(':') {
+        Some(rest) => (true, rest),
+        None => (false, rest),
+    };
+
+    let op = match rest.chars().next() {
+        None if !colon => None,
+        Some('-') => Some(ParamOp::Default {
+            word: lex_word(&rest[1..], quoted)?,
+            colon,
+        }),
+        Some('=') => Some(ParamOp::Assign {
+            word: lex_word(&rest[1..], quoted)?,
+            colon,
+        }),
+        Some('?') => Some(ParamOp::Error {
+            word: lex_word(&rest[1..], quoted)?,
+            colon,
+        }),
+        Some('+') => Some(ParamOp::Alternative {
+            word: lex_word(&rest[1..], quoted)?,
+            colon,
+        }),
+        Some('#') if !colon => {
+            let (longest, pattern) = match rest.strip_prefix("##") {
+                Some(pattern) => (true, pattern),
+                None => (false, &rest[1..]),
+            };
+
+            Some(ParamOp::RemovePrefix {
+                pattern: lex_word(pattern, false)?,
+                longest,
+            })
+        }
+        Some('%') if !colon => {
+            let (longest, pattern) = match rest.strip_prefix("%%") {
+                Some(pattern) => (true, pattern),
+                None => (false, &rest[1..]),
+            };
+
+            Some(ParamOp::RemoveSuffix {
+                pattern: lex_word(pattern, false)?,
+                longest,
+            })
+        }
+        Some('/') if !colon => {
+            let rest = &rest[1..];
+
+            let (mode, rest) = match rest.chars().next() {
+                Some('/') => (ReplaceMode::All, &rest[1..]),
+                Some('#') => (ReplaceMode::Prefix, &rest[1..]),
+                Some('%') => (ReplaceMode::Suffix, &rest[1..]),
+                _ => (ReplaceMode::First, rest),
+            };
+
+            let (pattern, replacement) = split_unquoted(rest, '/');
+
+            Some(ParamOp::Replace {
+                pattern: lex_word(pattern, false)?,
+                replacement: lex_word(replacement.unwrap_or(""), quoted)?,
+                mode,
+            })
+        }
+        _ => return Err(bad_substitution()),
+    };
+
+    Ok(Parameter {
+        name: name.to_string(),
+        index,
+        op: op.map(Box::new),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::WordPart;
+
+    fn literal(text: &str) -> WordPart {
+        WordPart::Literal(text.to_string())
+    }
+
+    fn quoted(text: &str) -> WordPart {
+        WordPart::Quoted(text.to_string())
+    }
+
+    fn word(parts: Vec<WordPart>) -> Token {
+        Token::Word(Word { parts })
+    }
+
+    #[test]
+    fn splits_words_on_blanks() {
+        assert_eq!(
+            tokenize("echo  a\tb\n").unwrap(),
+            vec![
+                word(vec![literal("echo")]),
+                word(vec![literal("a")]),
+                word(vec![literal("b")]),
+                Token::Newline,
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_quoted_text_in_one_word() {
+        assert_eq!(
+            tokenize("'a  b'\"c d\"e").unwrap(),
+            vec![word(vec![quoted("a  bc d"), literal("e")])]
+        );
+        assert_eq!(tokenize("''").unwrap(), vec![word(vec![quoted("")])]);
+    }
+
+    #[test]
+    fn backslash_quotes_the_next_character() {
+        assert_eq!(
+            tokenize("a\\ b").unwrap(),
+            vec![word(vec![literal("a"), quoted(" "), literal("b")])]
+        );
+        assert_eq!(tokenize("a\\\nb").unwrap(), vec![word(vec![literal("ab")])]);
+        assert_eq!(
+            tokenize("\"\\\"\\y\"").unwrap(),
+            vec![word(vec![quoted("\"\\y")])]
+        );
+    }
+
+    #[test]
+    fn recognizes_pipes_and_redirections() {
+        assert_eq!(
+            tokenize("a|b 2>f >>g").unwrap(),
+            vec![
+                word(vec![literal("a")]),
+                Token::Operator(Operator::Pipe),
+                word(vec![literal("b")]),
+                Token::Redirect {
+                    fd: Some(2),
+                    op: RedirectOp::Write,
+                },
+                word(vec![literal("f")]),
+                Token::Redirect {
+                    fd: None,
+                    op: RedirectOp::Append,
+                },
+                word(vec![literal("g")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn recognizes_descriptor_redirections() {
+        let ops: Vec<(Option<u32>, RedirectOp)> = tokenize("a 2>&1 <&- &>f &>>g 3<>h >&f")
+            .unwrap()
+            .into_iter()
+            .filter_map(|token| match token {
+                Token::Redirect { fd, op } => Some((fd, op)),
+                _ => None,
+            })
+            .collect();
+
+        assert_eq!(
+            ops,
+            [
+                (Some(2), RedirectOp::DupOutput),
+                (None, RedirectOp::DupInput),
+                (None, RedirectOp::WriteBoth),
+                (None, RedirectOp::AppendBoth),
+                (Some(3), RedirectOp::ReadWrite),
+                (None, RedirectOp::DupOutput),
+            ]
+        );
+    }
+
+    #[test]
+    fn recognizes_list_operators() {
+        assert_eq!(
+            tokenize("a&&b||c;d").unwrap(),
+            vec![
+                word(vec![literal("a")]),
+                Token::Operator(Operator::And),
+                word(vec![literal("b")]),
+                Token::Operator(Operator::Or),
+                word(vec![literal("c")]),
+                Token::Operator(Operator::Semi),
+                word(vec![literal("d")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn reads_parameters() {
+        let param = |name: &str, index: Option<&str>, quoted| WordPart::Param {
+            param: Parameter {
+                name: name.to_string(),
+                index: index.map(str::to_string),
+                op: None,
+            },
+            quoted,
+        };
+
+        assert_eq!(
+            tokenize("$x ${y}z $? ${PIPESTATUS[@]}").unwrap(),
+            vec![
+                word(vec![param("x", None, false)]),
+                word(vec![param("y", None, false), literal("z")]),
+                word(vec![param("?", None, false)]),
+                word(vec![param("PIPESTATUS", Some("@"), false)]),
+            ]
+        );
+        assert_eq!(
+            tokenize("\"a$x\"'$y'").unwrap(),
+            vec![word(vec![
+                quoted("a"),
+                param("x", None, true),
+                quoted("$y"),
+            ])]
+        );
+        assert_eq!(
+            tokenize("$ $12 ${10} $# $@ \"$\"").unwrap(),
+            vec![
+                word(vec![literal("$")]),
+                word(vec![param("1", None, false), literal("2")]),
+                word(vec![param("10", None, false)]),
+                word(vec![param("#", None, false)]),
+                word(vec![param("@", None, false)]),
+                word(vec![quoted("$")]),
+            ]
+        );
+        assert!(matches!(tokenize("${x"), Err(ParseError::Incomplete(_))));
+        assert!(matches!(
+            tokenize("${1x}"),
+            Err(ParseError::BadSubstitution(_))
+        ));
+    }
+
+    #[test]
+    fn reads_parameter_operators() {
+        let op = |source: &str| match tokenize(source).unwrap().remove(0) {
+            Token::Word(word) => match word.parts.into_iter().next() {
+                Some(WordPart::Param { param, .. }) => param.op.map(|op| *op),
+                part => panic!("{}: {:?}", source, part),
+            },
+            token => panic!("{}: {:?}", source, token),
+        };
+
+        assert_eq!(op("${#x}"), Some(ParamOp::Length));
+        assert!(matches!(
+            op("${x:-a b}"),
+            Some(ParamOp::Default { colon: true, word }) if word.parts == [literal("a b")]
+        ));
+        assert!(matches!(
+            op("${x=}"),
+            Some(ParamOp::Assign { colon: false, .. })
+        ));
+        assert!(matches!(
+            op("${x##*/}"),
+            Some(ParamOp::RemovePrefix { longest: true, .. })
+        ));
+        assert!(matches!(
+            op("${x%.c}"),
+            Some(ParamOp::RemoveSuffix { longest: false, .. })
+        ));
+        assert!(matches!(
+            op("${x//a/b}"),
+            Some(ParamOp::Replace {
+                mode: ReplaceMode::All,
+                ..
+            })
+        ));
+        assert!(matches!(
+            op("${x/#a}"),
+            Some(ParamOp::Replace { mode: ReplaceMode::Prefix, replacement, .. })
+                if replacement.parts.is_empty()
+        ));
+        assert!(matches!(
+            tokenize("${x:}"),
+            Err(ParseError::BadSubstitution(_))
+        ));
+    }
+
+    #[test]
+    fn reads_arithmetic() {
+        assert_eq!(
+            tokenize("((x+(1)))").unwrap(),
+            vec![Token::Arith(Word {
+                parts: vec![quoted("x+(1)")],
+            })]
+        );
+        assert_eq!(
+            tokenize("a$((2*3))").unwrap(),
+            vec![word(vec![
+                literal("a"),
+                WordPart::Arith {
+                    expr: Word {
+                        parts: vec![quoted("2*3")],
+                    },
+                    quoted: false,
+                },
+            ])]
+        );
+        assert!(matches!(
+            tokenize("$((1+2)"),
+            Err(ParseError::Incomplete(_))
+        ));
+    }
+
+    fn heredoc_body(source: &str) -> Word {
+        let tokens = tokenize(source).unwrap();
+
+        let Some(i) = tokens.iter().position(|token| {
+            matches!(
+                token,
+                Token::Redirect {
+                    op: RedirectOp::HereDoc,
+                    ..
+                }
+            )
+        }) else {
+            panic!("no here-document in {:?}", tokens);
+        };
+
+        match &tokens[i + 1] {
+            Token::Word(word) => word.clone(),
+            token => panic!("not a here-document body: {:?}", token),
+        }
+    }
+
+    #[test]
+    fn reads_here_documents_after_the_line() {
+        let body = heredoc_body("cat <<EOF; echo done\nhi $x\nEOF\n");
+        assert_eq!(body.expression(), "hi ${x}\n");
+        assert!(
+            body.parts
+                .iter()
+                .any(|part| matches!(part, WordPart::Param { quoted: true, .. }))
+        );
+
+        let body = heredoc_body("cat <<'EOF'\nhi $x\nEOF\n");
+        assert_eq!(body.parts, [quoted("hi $x\n")]);
+
+        let body = heredoc_body("cat <<-EOF\n\t\tindented\n\tEOF\n");
+        assert_eq!(body.parts, [quoted("indented\n")]);
+
+        assert!(matches!(
+            tokenize("cat <<EOF\nhi\n"),
+            Err(ParseError::HereDoc(delimiter)) if delimiter == "EOF"
+        ));
+        assert_eq!(
+            tokenize("cat <<<$x").unwrap()[1],
+            Token::Redirect {
+                fd: None,
+                op: RedirectOp::HereString,
+            }
+        );
+    }
+
+    #[test]
+    fn reports_unterminated_quotes() {
+        for (input, quote) in [("'abc", "'"), ("\"abc", "\""), ("a\"b\\\"", "\"")] {
+            match tokenize(input) {
+                Err(ParseError::Incomplete(found)) => assert_eq!(found, quote, "{}", input),
+                other => panic!("{}: {:?}", input, other),
+            }
+        }
+    }
+}
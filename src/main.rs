@@ -1,76 +1,101 @@
+mod arithmetic;
 mod arrow_navigaton;
+mod ast;
 mod autocompletion;
+mod brace;
+mod builtins;
+mod executor;
+mod expansion;
+mod glob;
+mod jobs;
+mod lexer;
+mod options;
+mod parameter;
+mod parser;
+mod pattern;
 mod pipeline;
-mod quoting;
+mod process;
 mod redirection;
+mod script;
+mod shell;
+mod signals;
+mod substitution;
+mod tilde;
+mod variables;
 
 use crate::arrow_navigaton::{Direction, move_history};
 use crate::autocompletion::{find_completions, find_lcp};
-use crate::pipeline::run_pipeline;
-use crate::quoting::parse_args;
-use crate::redirection::parse_redirection;
+use crate::executor::run_list;
+use crate::parser::parse;
+use crate::script::{run_file, run_source, stdin_source};
+use crate::shell::{Flow, Shell};
+use crate::signals::init_signals;
 
 use crossterm::{
     ExecutableCommand, cursor,
     event::{self, Event, KeyCode, KeyModifiers},
     terminal::{Clear, ClearType, disable_raw_mode, enable_raw_mode},
 };
-use std::fs::{File, OpenOptions};
-use std::io::{Read, Write};
-use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
-use std::process::{Command, Stdio, exit};
-use std::result::Result::Ok;
-use std::{env, fs};
-use std::{env::set_current_dir, io};
-
-enum Cmd {
-    Exit,
-    Echo,
-    Type,
-    Run,
-    Pwd,
-    Cd,
-    History,
-}
+use std::env;
+use std::io::{self, Write};
+use std::process::exit;
 
-impl Cmd {
-    fn parse(s: &str) -> Self {
-        match s {
-            "exit" => Cmd::Exit,
-            "echo" => Cmd::Echo,
-            "type" => Cmd::Type,
-            "pwd" => Cmd::Pwd,
-            "cd" => Cmd::Cd,
-            "history" => Cmd::History,
-            _ => Cmd::Run,
+fn main() {
+    let mut shell = Shell::new();
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let status = match args.first().map(String::as_str) {
+        None if unsafe { libc::isatty(0) } == 1 => interactive(&mut shell),
+        None => {
+            init_signals(false);
+            run_source(&mut shell, stdin_source())
         }
-    }
-}
+        Some("-c") => {
+            let Some(command) = args.get(1) else {
+                eprintln!("{}: -c: option requires an argument", shell.arg0);
+                exit(2);
+            };
+
+            if let Some(arg0) = args.get(2) {
+                shell.arg0 = arg0.clone();
+            }
+            shell.positional = args.iter().skip(3).cloned().collect();
 
-fn main() {
-    let mut stdout = io::stdout();
-    let mut local_history = Vec::<String>::new();
+            init_signals(false);
+            run_source(&mut shell, command.as_bytes())
+        }
+        Some("-s") => {
+            shell.positional = args[1..].to_vec();
 
-    if let Ok(histfile_var) = env::var("HISTFILE") {
-        let mut file = File::open(histfile_var).unwrap();
-        let mut contents = String::new();
+            init_signals(false);
+            run_source(&mut shell, stdin_source())
+        }
+        Some(path) => {
+            init_signals(false);
+            run_file(&mut shell, path, &args[1..])
+        }
+    };
 
-        file.read_to_string(&mut contents).unwrap();
+    exit(status);
+}
 
-        for lines in contents.lines() {
-            local_history.push(lines.to_string());
-        }
-    }
+fn interactive(shell: &mut Shell) -> i32 {
+    let mut stdout = io::stdout();
 
-    let index_for_exiting = local_history.len();
+    shell.interactive = true;
+    shell.load_history();
+    init_signals(true);
+    shell.jobs.enable_job_control();
+    let mut pending = String::new();
 
     loop {
+        shell.jobs.notify();
         enable_raw_mode().unwrap();
 
-        let mut history_index = local_history.len();
+        let mut history_index = shell.history.len();
+        let prompt = if pending.is_empty() { "$ " } else { "> " };
 
-        print!("$ ");
+        print!("{}", prompt);
         stdout.flush().unwrap();
 
         let mut input_buffer = String::new();
@@ -98,7 +123,7 @@ fn main() {
                             stdout.execute(cursor::MoveToColumn(0)).unwrap();
                             stdout.execute(Clear(ClearType::CurrentLine)).unwrap();
 
-                            print!("$ {}", input_buffer);
+                            print!("{}{}", prompt, input_buffer);
                             stdout.flush().unwrap();
 
                             tab_press_count = 0;
@@ -113,7 +138,7 @@ fn main() {
                                     input_buffer = lcp;
                                     cursor_position = input_buffer.len();
 
-                                    print!("$ {input_buffer}");
+                                    print!("{prompt}{input_buffer}");
                                 }
 
                                 print!("\x07");
@@ -124,7 +149,7 @@ fn main() {
                                 let list = matches.join("  ");
                                 print!("{}\r\n", list);
 
-                                print!("$ {}", input_buffer);
+                                print!("{}{}", prompt, input_buffer);
                                 stdout.flush().unwrap();
                             }
                         } else {
@@ -134,25 +159,24 @@ fn main() {
                         }
                     }
                     KeyCode::Enter => execute_command = true,
-                    KeyCode::Backspace => {
-                        if cursor_position > 0 {
-                            input_buffer.remove(cursor_position - 1);
-                            cursor_position -= 1;
+                    KeyCode::Backspace if cursor_position > 0 => {
+                        input_buffer.remove(cursor_position - 1);
+                        cursor_position -= 1;
 
-                            stdout.execute(cursor::MoveToColumn(0)).unwrap();
-                            stdout.execute(Clear(ClearType::CurrentLine)).unwrap();
+                        stdout.execute(cursor::MoveToColumn(0)).unwrap();
+                        stdout.execute(Clear(ClearType::CurrentLine)).unwrap();
 
-                            print!("$ {}", input_buffer);
+                        print!("{}{}", prompt, input_buffer);
 
-                            let new_pos = (cursor_position + 2) as u16;
-                            stdout.execute(cursor::MoveToColumn(new_pos)).unwrap();
+                        let new_pos = (cursor_position + prompt.len()) as u16;
+                        stdout.execute(cursor::MoveToColumn(new_pos)).unwrap();
 
-                            stdout.flush().unwrap();
-                        }
+                        stdout.flush().unwrap();
                     }
                     KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                         println!("^C");
                         input_buffer.clear();
+                        pending.clear();
                         break;
                     }
                     KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
@@ -174,14 +198,14 @@ fn main() {
                     }
                     KeyCode::Up => move_history(
                         Direction::Up,
-                        &local_history,
+                        &shell.history,
                         &mut input_buffer,
                         &mut history_index,
                         &mut stdout,
                     ),
                     KeyCode::Down => move_history(
                         Direction::Down,
-                        &local_history,
+                        &shell.history,
                         &mut input_buffer,
                         &mut history_index,
                         &mut stdout,
@@ -193,202 +217,30 @@ fn main() {
                     disable_raw_mode().unwrap();
                     println!();
 
-                    let input = input_buffer.trim();
-                    local_history.push(input.to_string());
+                    let input = if pending.is_empty() {
+                        input_buffer
+                    } else {
+                        format!("{}\n{}", pending, input_buffer)
+                    };
 
-                    if !input.is_empty() {
-                        if input.contains('|') {
-                            run_pipeline(input);
-                        } else {
-                            let parts = parse_args(input);
-
-                            if !parts.is_empty() {
-                                match Cmd::parse(&parts[0]) {
-                                    Cmd::Exit => {
-                                        if let Ok(histfile_var) = env::var("HISTFILE") {
-                                            let mut file = OpenOptions::new()
-                                                .create(true)
-                                                .append(true)
-                                                .open(histfile_var)
-                                                .unwrap();
-
-                                            for (i, cmd) in local_history.iter().enumerate() {
-                                                if i >= index_for_exiting {
-                                                    writeln!(file, "{}", cmd).unwrap();
-                                                }
-                                            }
-                                        }
-                                        exit(0);
-                                    }
-                                    Cmd::Echo => {
-                                        let mut args = parts[1..].to_vec();
-
-                                        let (stdout_opt, _stderr_opt) =
-                                            parse_redirection(&mut args);
-
-                                        let output_text = args.join(" ");
-
-                                        match stdout_opt {
-                                            Some(mut file) => {
-                                                writeln!(file, "{}", output_text).unwrap()
-                                            }
-                                            None => {
-                                                println!("{}", output_text);
-                                            }
-                                        }
-                                    }
-                                    Cmd::Type => match parts[1].as_str() {
-                                        "exit" | "echo" | "type" | "pwd" | "cd" | "history" => {
-                                            println!("{} is a shell builtin", parts[1])
-                                        }
-                                        _ => {
-                                            let mut found = false;
-
-                                            if let Ok(path_var) = env::var("PATH") {
-                                                for path in path_var.split(':') {
-                                                    let full_path =
-                                                        format!("{}/{}", path, parts[1]);
-
-                                                    if Path::new(&full_path).exists() {
-                                                        if let Ok(metadata) =
-                                                            fs::metadata(&full_path)
-                                                        {
-                                                            if metadata.permissions().mode() & 0o111
-                                                                != 0
-                                                            {
-                                                                println!(
-                                                                    "{} is {}",
-                                                                    parts[1], full_path
-                                                                );
-                                                                found = true;
-                                                                break;
-                                                            }
-                                                        }
-                                                    }
-                                                }
-                                            }
-                                            if !found {
-                                                println!("{}: not found", parts[1]);
-                                            }
-                                        }
-                                    },
-                                    Cmd::Run => {
-                                        let program_name = &parts[0];
-                                        let mut args = parts[1..].to_vec();
-
-                                        let (stdout_opt, stderr_opt) = parse_redirection(&mut args);
-
-                                        let stdout_dest = match stdout_opt {
-                                            Some(f) => Stdio::from(f),
-                                            None => Stdio::inherit(),
-                                        };
-
-                                        let stderr_dest = match stderr_opt {
-                                            Some(f) => Stdio::from(f),
-                                            None => Stdio::inherit(),
-                                        };
-
-                                        match Command::new(program_name)
-                                            .args(args)
-                                            .stdout(stdout_dest)
-                                            .stderr(stderr_dest)
-                                            .spawn()
-                                        {
-                                            Ok(mut child) => {
-                                                let _ = child.wait();
-                                            }
-                                            Err(_) => {
-                                                println!("{}: command not found", input)
-                                            }
-                                        }
-                                    }
-                                    Cmd::Pwd => {
-                                        let path = env::current_dir().expect("Not existing");
-                                        println!("{}", path.display());
-                                    }
-                                    Cmd::Cd => match parts[1].as_str() {
-                                        "~" => {
-                                            let home = env::home_dir().expect("No home dir found");
-                                            set_current_dir(home)
-                                                .expect("Failed changing directory")
-                                        }
-                                        _ => {
-                                            if Path::new(&parts[1]).exists() {
-                                                set_current_dir(&parts[1])
-                                                    .expect("Failed changing directory")
-                                            } else {
-                                                println!(
-                                                    "cd: {}: No such file or directory",
-                                                    parts[1]
-                                                )
-                                            }
-                                        }
-                                    },
-                                    Cmd::History => {
-                                        if parts.len() == 1 {
-                                            for (i, cmd) in local_history.iter().enumerate() {
-                                                println!("  {}  {}", i + 1, cmd);
-                                            }
-                                        } else {
-                                            if let Ok(limiter) = parts[1].parse::<u8>() {
-                                                for (i, last_cmd) in local_history
-                                                    .iter()
-                                                    .enumerate()
-                                                    .rev()
-                                                    .take(limiter.into())
-                                                    .rev()
-                                                {
-                                                    println!("  {}  {}", i + 1, last_cmd);
-                                                }
-                                            } else {
-                                                match parts[1].as_str() {
-                                                    "-r" => {
-                                                        let mut file =
-                                                            File::open(&parts[2]).unwrap();
-                                                        let mut contents = String::new();
-
-                                                        file.read_to_string(&mut contents).unwrap();
-
-                                                        for cmd in contents.lines() {
-                                                            local_history.push(cmd.to_string());
-                                                        }
-                                                    }
-                                                    "-w" => {
-                                                        let mut file = OpenOptions::new()
-                                                            .write(true)
-                                                            .create(true)
-                                                            .open(&parts[2])
-                                                            .unwrap();
-
-                                                        for lines in &local_history {
-                                                            writeln!(file, "{}", lines).unwrap();
-                                                        }
-                                                    }
-                                                    "-a" => {
-                                                        let mut file = OpenOptions::new()
-                                                            .append(true)
-                                                            .create(true)
-                                                            .open(&parts[2])
-                                                            .unwrap();
-
-                                                        let mut iter = local_history
-                                                            .split_inclusive(|cmd_block| {
-                                                                cmd_block.contains("history -a")
-                                                            });
-
-                                                        let last_element_history =
-                                                            iter.next_back().unwrap().to_vec();
-
-                                                        for lines in last_element_history {
-                                                            writeln!(file, "{}", lines).unwrap();
-                                                        }
-                                                    }
-                                                    _ => todo!(),
-                                                }
-                                            }
-                                        }
+                    match parse(&input) {
+                        Err(e) if e.needs_more_input() => pending = input,
+                        result => {
+                            pending.clear();
+                            shell.history.push(input.trim().to_string());
+
+                            match result {
+                                Ok(list) => {
+                                    run_list(shell, &list);
+
+                                    if shell.flow == Some(Flow::Abort) {
+                                        shell.flow = None;
                                     }
                                 }
+                                Err(e) => {
+                                    eprintln!("{}", e);
+                                    shell.last_status = 2;
+                                }
                             }
                         }
                     }
@@ -0,0 +1,37 @@
+#[derive(Default)]
+pub struct Options {
+    pub dotglob: bool,
+    pub extglob: bool,
+    pub failglob: bool,
+    pub globstar: bool,
+    pub nullglob: bool,
+    pub noclobber: bool,
+    pub noglob: bool,
+    pub pipefail: bool,
+}
+
+pub const SHOPT_NAMES: &[&str] = &["dotglob", "extglob", "failglob", "globstar", "nullglob"];
+
+pub const SET_NAMES: &[&str] = &["noclobber", "noglob", "pipefail"];
+
+impl Options {
+    pub fn shopt(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "dotglob" => Some(&mut self.dotglob),
+            "extglob" => Some(&mut self.extglob),
+            "failglob" => Some(&mut self.failglob),
+            "globstar" => Some(&mut self.globstar),
+            "nullglob" => Some(&mut self.nullglob),
+            _ => None,
+        }
+    }
+
+    pub fn set(&mut self, name: &str) -> Option<&mut bool> {
+        match name {
+            "noclobber" => Some(&mut self.noclobber),
+            "noglob" => Some(&mut self.noglob),
+            "pipefail" => Some(&mut self.pipefail),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,279 @@
+use crate::ast::{ParamOp, Parameter, ReplaceMode};
+use crate::expansion::{ExpansionError, expand_pattern, expand_word};
+use crate::pattern::Pattern;
+use crate::shell::Shell;
+use crate::variables::is_valid_name;
+
+pub fn expand_parameter(
+    shell: &mut Shell,
+    param: &Parameter,
+) -> Result<Vec<String>, ExpansionError> {
+    let values = lookup(shell, param);
+
+    let Some(op) = &param.op else {
+        return Ok(values.unwrap_or_default());
+    };
+
+    let is_null = |values: &Option<Vec<String>>, colon: bool| match values {
+        None => true,
+        Some(values) => colon && values.iter().all(String::is_empty),
+    };
+
+    match op.as_ref() {
+        ParamOp::Length => {
+            let values = values.unwrap_or_default();
+
+            let length = match param.index.as_deref() {
+                _ if matches!(param.name.as_str(), "@" | "*") => values.len(),
+                Some("@" | "*") => values.len(),
+                _ => values.first().map_or(0, |value| value.chars().count()),
+            };
+
+            Ok(vec![length.to_string()])
+        }
+        ParamOp::Default { word, colon } => {
+            if is_null(&values, *colon) {
+                Ok(vec![expand_word(shell, word)?])
+            } else {
+                Ok(values.unwrap_or_default())
+            }
+        }
+        ParamOp::Assign { word, colon } => {
+            if !is_null(&values, *colon) {
+                return Ok(values.unwrap_or_default());
+            }
+
+            if !is_valid_name(&param.name) || param.index.is_some() {
+                return Err(ExpansionError::BadAssignment(param.name.clone()));
+            }
+
+            let value = expand_word(shell, word)?;
+            shell.vars.set(&param.name, &value);
+
+            Ok(vec![value])
+        }
+        ParamOp::Error { word, colon } => {
+            if !is_null(&values, *colon) {
+                return Ok(values.unwrap_or_default());
+            }
+
+            let message = expand_word(shell, word)?;
+
+            let message = if message.is_empty() {
+                "parameter null or not set".to_string()
+            } else {
+                message
+            };
+
+            Err(ExpansionError::Unset(param.name.clone(), message))
+        }
+        ParamOp::Alternative { word, colon } => {
+            if is_null(&values, *colon) {
+                Ok(Vec::new())
+            } else {
+                Ok(vec![expand_word(shell, word)?])
+            }
+        }
+        ParamOp::RemovePrefix { pattern, longest } => {
+            let pattern = Pattern::new(&expand_pattern(shell, pattern)?, shell.options.extglob);
+
+            Ok(map_values(values, |value| {
+                remove_prefix(value, &pattern, *longest)
+            }))
+        }
+        ParamOp::RemoveSuffix { pattern, longest } => {
+            let pattern = Pattern::new(&expand_pattern(shell, pattern)?, shell.options.extglob);
+
+            Ok(map_values(values, |value| {
+                remove_suffix(value, &pattern, *longest)
+            }))
+        }
+        ParamOp::Replace {
+            pattern,
+            replacement,
+            mode,
+        } => {
+            let pattern = Pattern::new(&expand_pattern(shell, pattern)?, shell.options.extglob);
+            let replacement = expand_word(shell, replacement)?;
+
+            Ok(map_values(values, |value| {
+                replace(value, &pattern, &replacement, *mode)
+            }))
+        }
+    }
+}
+
+fn lookup(shell: &Shell, param: &Parameter) -> Option<Vec<String>> {
+    let values = match param.name.as_str() {
+        "?" => vec![shell.last_status.to_string()],
+        "$" => vec![std::process::id().to_string()],
+        "!" => vec![shell.last_background?.to_string()],
+        "#" => vec![shell.positional.len().to_string()],
+        "@" | "*" => return Some(shell.positional.clone()),
+        "0" => vec![shell.arg0.clone()],
+        "PIPESTATUS" => shell.pipestatus.iter().map(i32::to_string).collect(),
+        name if name.chars().all(|c| c.is_ascii_digit()) => {
+            let index = name.parse::<usize>().ok()?.checked_sub(1)?;
+            vec![shell.positional.get(index)?.clone()]
+        }
+        name => vec![shell.vars.get(name)?.to_string()],
+    };
+
+    match param.index.as_deref() {
+        None => values.into_iter().next().map(|value| vec![value]),
+        Some("@" | "*") => Some(values),
+        Some(index) => {
+            let i = index.trim().parse::<usize>().unwrap_or(0);
+            values.into_iter().nth(i).map(|value| vec![value])
+        }
+    }
+}
+
+fn map_values(values: Option<Vec<String>>, f: impl Fn(&str) -> String) -> Vec<String> {
+    values
+        .unwrap_or_default()
+        .iter()
+        .map(|value| f(value))
+        .collect()
+}
+
+fn boundaries(value: &str) -> Vec<usize> {
+    value
+        .char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(value.len()))
+        .collect()
+}
+
+fn remove_prefix(value: &str, pattern: &Pattern, longest: bool) -> String {
+    let mut ends = boundaries(value);
+
+    if longest {
+        ends.reverse();
+    }
+
+    for end in ends {
+        if pattern.matches(&value[..end]) {
+            return value[end..].to_string();
+        }
+    }
+
+    value.to_string()
+}
+
+fn remove_suffix(value: &str, pattern: &Pattern, longest: bool) -> String {
+    let mut starts = boundaries(value);
+
+    if !longest {
+        starts.reverse();
+    }
+
+    for start in starts {
+        if pattern.matches(&value[start..]) {
+            return value[..start].to_string();
+        }
+    }
+
+    value.to_string()
+}
+
+fn replace(value: &str, pattern: &Pattern, replacement: &str, mode: ReplaceMode) -> String {
+    let bounds = boundaries(value);
+
+    match mode {
+        ReplaceMode::Prefix => {
+            for &end in bounds.iter().rev() {
+                if pattern.matches(&value[..end]) {
+                    return format!("{}{}", replacement, &value[end..]);
+                }
+            }
+
+            value.to_string()
+        }
+        ReplaceMode::Suffix => {
+            for &start in &bounds {
+                if pattern.matches(&value[start..]) {
+                    return format!("{}{}", &value[..start], replacement);
+                }
+            }
+
+            value.to_string()
+        }
+        ReplaceMode::First | ReplaceMode::All => {
+            let mut result = String::new();
+            let mut i = 0;
+
+            while i < bounds.len() - 1 {
+                let start = bounds[i];
+
+                let found = bounds[i + 1..]
+                    .iter()
+                    .rev()
+                    .position(|&end| pattern.matches(&value[start..end]))
+                    .map(|offset| bounds.len() - 1 - offset);
+
+                match found {
+                    Some(end_index) => {
+                        result.push_str(replacement);
+                        i = end_index;
+
+                        if mode == ReplaceMode::First {
+                            result.push_str(&value[bounds[i]..]);
+                            return result;
+                        }
+                    }
+                    None => {
+                        result.push_str(&value[start..bounds[i + 1]]);
+                        i += 1;
+                    }
+                }
+            }
+
+            result
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pattern(text: &str) -> Pattern {
+        Pattern::new(text, false)
+    }
+
+    #[test]
+    fn removes_shortest_and_longest_prefix() {
+        assert_eq!(remove_prefix("a/b/c", &pattern("*/"), false), "b/c");
+        assert_eq!(remove_prefix("a/b/c", &pattern("*/"), true), "c");
+        assert_eq!(remove_prefix("héllo", &pattern("h?"), false), "llo");
+        assert_eq!(remove_prefix("abc", &pattern("x"), true), "abc");
+        assert_eq!(remove_prefix("", &pattern("*"), true), "");
+    }
+
+    #[test]
+    fn removes_shortest_and_longest_suffix() {
+        assert_eq!(
+            remove_suffix("file.tar.gz", &pattern(".*"), false),
+            "file.tar"
+        );
+        assert_eq!(remove_suffix("file.tar.gz", &pattern(".*"), true), "file");
+        assert_eq!(remove_suffix("abc", &pattern("x"), false), "abc");
+    }
+
+    #[test]
+    fn replaces_by_mode() {
+        let banana = |from: &str, to: &str, mode| replace("banana", &pattern(from), to, mode);
+
+        assert_eq!(banana("a", "o", ReplaceMode::First), "bonana");
+        assert_eq!(banana("a", "o", ReplaceMode::All), "bonono");
+        assert_eq!(banana("an", "", ReplaceMode::All), "ba");
+        assert_eq!(banana("a*", "x", ReplaceMode::First), "bx");
+        assert_eq!(banana("b", "c", ReplaceMode::Prefix), "canana");
+        assert_eq!(banana("n", "c", ReplaceMode::Prefix), "banana");
+        assert_eq!(banana("*n", "", ReplaceMode::Prefix), "a");
+        assert_eq!(banana("a", "e", ReplaceMode::Suffix), "banane");
+        assert_eq!(banana("x", "y", ReplaceMode::All), "banana");
+        assert_eq!(replace("", &pattern("x"), "y", ReplaceMode::First), "");
+    }
+}
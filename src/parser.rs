@@ -0,0 +1,891 @@
+use thiserror::Error;
+
+use std::mem;
+use std::rc::Rc;
+
+use crate::ast::{
+    AndOr, CaseItem, CaseTerminator, Command, Compound, Connector, List, Pipeline, Redirect,
+    RedirectOp, SimpleCommand, Word, WordPart,
+};
+use crate::lexer::{Operator, Token, tokenize};
+use crate::variables::is_valid_name;
+
+pub const KEYWORDS: &[&str] = &[
+    "!", "case", "do", "done", "elif", "else", "esac", "fi", "for", "function", "if", "in", "then",
+    "until", "while", "{", "}",
+];
+
+const TERMINATORS: &[&str] = &["do", "done", "elif", "else", "esac", "fi", "then", "}"];
+
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("unexpected EOF while looking for matching `{0}'")]
+    Incomplete(String),
+    #[error("syntax error: unexpected end of file")]
+    UnexpectedEof,
+    #[error("syntax error near unexpected token `{0}'")]
+    Unexpected(String),
+    #[error("{0}: bad substitution")]
+    BadSubstitution(String),
+    #[error("here-document delimited by end-of-file (wanted `{0}')")]
+    HereDoc(String),
+}
+
+impl ParseError {
+    pub fn needs_more_input(&self) -> bool {
+        matches!(
+            self,
+            ParseError::HereDoc(_) | ParseError::Incomplete(_) | ParseError::UnexpectedEof
+        )
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+pub fn parse(input: &str) -> Result<List, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        pos: 0,
+    };
+
+    parser.list()
+}
+
+fn describe(token: &Token) -> String {
+    match token {
+        Token::Word(word) => word.to_string(),
+        Token::Arith(expr) => format!("(({}))", expr.expression()),
+        Token::Operator(operator) => match operator {
+            Operator::Pipe => "|".to_string(),
+            Operator::PipeBoth => "|&".to_string(),
+            Operator::Background => "&".to_string(),
+            Operator::Semi => ";".to_string(),
+            Operator::DoubleSemi => ";;".to_string(),
+            Operator::SemiAnd => ";&".to_string(),
+            Operator::DoubleSemiAnd => ";;&".to_string(),
+            Operator::And => "&&".to_string(),
+            Operator::Or => "||".to_string(),
+            Operator::OpenParen => "(".to_string(),
+            Operator::CloseParen => ")".to_string(),
+        },
+        Token::Redirect { op, .. } => op.to_string(),
+        Token::Newline => "newline".to_string(),
+    }
+}
+
+fn keyword(token: Option<&Token>) -> Option<&str> {
+    match token {
+        Some(Token::Word(word)) => match &word.parts[..] {
+            [WordPart::Literal(text)] => Some(text),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn case_terminator(token: Option<&Token>) -> Option<CaseTerminator> {
+    match token {
+        Some(Token::Operator(Operator::DoubleSemi)) => Some(CaseTerminator::Break),
+        Some(Token::Operator(Operator::SemiAnd)) => Some(CaseTerminator::FallThrough),
+        Some(Token::Operator(Operator::DoubleSemiAnd)) => Some(CaseTerminator::Continue),
+        _ => None,
+    }
+}
+
+fn is_close_paren(token: Option<&Token>) -> bool {
+    token == Some(&Token::Operator(Operator::CloseParen))
+}
+
+fn is_keyword(token: Option<&Token>, expected: &str) -> bool {
+    keyword(token) == Some(expected)
+}
+
+fn split_arith(expr: &Word) -> Vec<Word> {
+    let mut words = Vec::new();
+    let mut current = Word::default();
+
+    for part in &expr.parts {
+        match part {
+            WordPart::Quoted(text) => {
+                for (index, piece) in text.split(';').enumerate() {
+                    if index > 0 {
+                        words.push(mem::take(&mut current));
+                    }
+
+                    if !piece.is_empty() {
+                        current.push_quoted(piece);
+                    }
+                }
+            }
+            part => current.parts.push(part.clone()),
+        }
+    }
+
+    words.push(current);
+    words
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn unexpected(&self) -> ParseError {
+        match self.peek() {
+            Some(token) => ParseError::Unexpected(describe(token)),
+            None => ParseError::UnexpectedEof,
+        }
+    }
+
+    fn skip_newlines(&mut self) {
+        while self.peek() == Some(&Token::Newline) {
+            self.pos += 1;
+        }
+    }
+
+    fn expect_keyword(&mut self, expected: &str) -> Result<(), ParseError> {
+        if !is_keyword(self.peek(), expected) {
+            return Err(self.unexpected());
+        }
+
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn list(&mut self) -> Result<List, ParseError> {
+        self.skip_newlines();
+
+        if self.peek().is_none() {
+            return Ok(List::default());
+        }
+
+        let list = self.compound_list(&[])?;
+
+        match self.peek() {
+            Some(_) => Err(self.unexpected()),
+            None => Ok(list),
+        }
+    }
+
+    fn compound_list(&mut self, terminators: &[&str]) -> Result<List, ParseError> {
+        let mut list = List::default();
+
+        loop {
+            self.skip_newlines();
+
+            let at_terminator = keyword(self.peek()).is_some_and(|kw| terminators.contains(&kw));
+
+            if self.peek().is_none()
+                || at_terminator
+                || case_terminator(self.peek()).is_some()
+                || is_close_paren(self.peek())
+            {
+                break;
+            }
+
+            list.items.push(self.and_or()?);
+
+            match self.peek() {
+                None | Some(Token::Newline) => {}
+                Some(Token::Operator(Operator::Semi)) => self.pos += 1,
+                Some(Token::Operator(Operator::Background)) => {
+                    self.pos += 1;
+
+                    if let Some(and_or) = list.items.last_mut() {
+                        and_or.background = true;
+                    }
+                }
+                Some(_)
+                    if case_terminator(self.peek()).is_some() || is_close_paren(self.peek()) => {}
+                Some(_) => return Err(self.unexpected()),
+            }
+        }
+
+        if list.items.is_empty() {
+            return Err(self.unexpected());
+        }
+
+        Ok(list)
+    }
+
+    fn and_or(&mut self) -> Result<AndOr, ParseError> {
+        let mut and_or = AndOr {
+            first: self.pipeline()?,
+            ..AndOr::default()
+        };
+
+        loop {
+            let connector = match self.peek() {
+                Some(Token::Operator(Operator::And)) => Connector::And,
+                Some(Token::Operator(Operator::Or)) => Connector::Or,
+                _ => return Ok(and_or),
+            };
+
+            self.pos += 1;
+            self.skip_newlines();
+            and_or.rest.push((connector, self.pipeline()?));
+        }
+    }
+
+    fn pipeline(&mut self) -> Result<Pipeline, ParseError> {
+        let mut pipeline = Pipeline::default();
+
+        while is_keyword(self.peek(), "!") {
+            self.pos += 1;
+            pipeline.negated = !pipeline.negated;
+        }
+
+        pipeline.commands.push(self.command()?);
+
+        loop {
+            match self.peek() {
+                Some(Token::Operator(Operator::Pipe)) => {}
+                Some(Token::Operator(Operator::PipeBoth)) => {
+                    if let Some(command) = pipeline.commands.last_mut() {
+                        command.redirect_stderr_to_stdout();
+                    }
+                }
+                _ => break,
+            }
+
+            self.pos += 1;
+            self.skip_newlines();
+            pipeline.commands.push(self.command()?);
+        }
+
+        Ok(pipeline)
+    }
+
+    fn command(&mut self) -> Result<Command, ParseError> {
+        if let Some(Token::Arith(expr)) = self.peek() {
+            let expr = expr.clone();
+            self.pos += 1;
+            return Ok(Command::Arith(expr));
+        }
+
+        if self.at_function_definition() {
+            return self.function_definition();
+        }
+
+        if self.peek() == Some(&Token::Operator(Operator::OpenParen)) {
+            self.pos += 1;
+            let list = self.compound_list(&[])?;
+
+            if !is_close_paren(self.peek()) {
+                return Err(self.unexpected());
+            }
+
+            self.pos += 1;
+            return self.compound_redirects(Compound::Subshell(list));
+        }
+
+        let compound = match keyword(self.peek()) {
+            Some("{") => {
+                self.pos += 1;
+                let list = self.compound_list(&["}"])?;
+                self.expect_keyword("}")?;
+                Compound::Group(list)
+            }
+            Some("if") => self.if_clause()?,
+            Some("while") => self.while_clause(false)?,
+            Some("until") => self.while_clause(true)?,
+            Some("for") => self.for_clause()?,
+            Some("case") => self.case_clause()?,
+            Some(kw) if TERMINATORS.contains(&kw) => return Err(self.unexpected()),
+            _ => return Ok(Command::Simple(self.simple_command()?)),
+        };
+
+        self.compound_redirects(compound)
+    }
+
+    fn compound_redirects(&mut self, compound: Compound) -> Result<Command, ParseError> {
+        let mut redirects = Vec::new();
+
+        while let Some(Token::Redirect { fd, op }) = self.peek() {
+            let (fd, op) = (*fd, *op);
+            self.pos += 1;
+            redirects.push(self.redirect(fd, op)?);
+        }
+
+        Ok(Command::Compound(compound, redirects))
+    }
+
+    fn at_function_definition(&self) -> bool {
+        match keyword(self.peek()) {
+            Some("function") => true,
+            Some(name) if !KEYWORDS.contains(&name) && !name.contains('=') => {
+                self.tokens.get(self.pos + 1) == Some(&Token::Operator(Operator::OpenParen))
+            }
+            _ => false,
+        }
+    }
+
+    fn function_definition(&mut self) -> Result<Command, ParseError> {
+        if is_keyword(self.peek(), "function") {
+            self.pos += 1;
+        }
+
+        let name = match self.next() {
+            Some(token) => match keyword(Some(&token)) {
+                Some(name) if !KEYWORDS.contains(&name) => name.to_string(),
+                _ => return Err(ParseError::Unexpected(describe(&token))),
+            },
+            None => return Err(ParseError::UnexpectedEof),
+        };
+
+        if self.peek() == Some(&Token::Operator(Operator::OpenParen)) {
+            self.pos += 1;
+
+            if self.peek() != Some(&Token::Operator(Operator::CloseParen)) {
+                return Err(self.unexpected());
+            }
+
+            self.pos += 1;
+        }
+
+        self.skip_newlines();
+
+        let start = self.pos;
+
+        match self.command()? {
+            body @ Command::Compound(..) => Ok(Command::Function(name, Rc::new(body))),
+            _ => Err(ParseError::Unexpected(describe(&self.tokens[start]))),
+        }
+    }
+
+    fn if_clause(&mut self) -> Result<Compound, ParseError> {
+        let mut branches = Vec::new();
+        let mut otherwise = None;
+
+        self.pos += 1;
+
+        loop {
+            let condition = self.compound_list(&["then"])?;
+            self.expect_keyword("then")?;
+
+            let body = self.compound_list(&["elif", "else", "fi"])?;
+            branches.push((condition, body));
+
+            match keyword(self.peek()) {
+                Some("elif") => self.pos += 1,
+                Some("else") => {
+                    self.pos += 1;
+                    otherwise = Some(self.compound_list(&["fi"])?);
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        self.expect_keyword("fi")?;
+
+        Ok(Compound::If {
+            branches,
+            otherwise,
+        })
+    }
+
+    fn while_clause(&mut self, until: bool) -> Result<Compound, ParseError> {
+        self.pos += 1;
+
+        let condition = self.compound_list(&["do"])?;
+        let body = self.do_group()?;
+
+        Ok(Compound::While {
+            until,
+            condition,
+            body,
+        })
+    }
+
+    fn for_clause(&mut self) -> Result<Compound, ParseError> {
+        self.pos += 1;
+
+        let name = match self.next() {
+            Some(Token::Arith(expr)) => return self.arith_for(&expr),
+            Some(token) => match keyword(Some(&token)) {
+                Some(name) if is_valid_name(name) => name.to_string(),
+                _ => return Err(ParseError::Unexpected(describe(&token))),
+            },
+            None => return Err(ParseError::UnexpectedEof),
+        };
+
+        self.skip_newlines();
+
+        let words = if is_keyword(self.peek(), "in") {
+            self.pos += 1;
+            let mut words = Vec::new();
+
+            while let Some(Token::Word(word)) = self.peek() {
+                words.push(word.clone());
+                self.pos += 1;
+            }
+
+            match self.peek() {
+                Some(Token::Operator(Operator::Semi) | Token::Newline) => self.pos += 1,
+                _ => return Err(self.unexpected()),
+            }
+
+            Some(words)
+        } else {
+            if self.peek() == Some(&Token::Operator(Operator::Semi)) {
+                self.pos += 1;
+            }
+
+            None
+        };
+
+        Ok(Compound::For {
+            name,
+            words,
+            body: self.do_group()?,
+        })
+    }
+
+    fn arith_for(&mut self, expr: &Word) -> Result<Compound, ParseError> {
+        let [init, condition, update]: [Word; 3] = split_arith(expr)
+            .try_into()
+            .map_err(|_| ParseError::Unexpected(format!("(({}))", expr)))?;
+
+        if self.peek() == Some(&Token::Operator(Operator::Semi)) {
+            self.pos += 1;
+        }
+
+        Ok(Compound::ArithFor {
+            init,
+            condition,
+            update,
+            body: self.do_group()?,
+        })
+    }
+
+    fn case_clause(&mut self) -> Result<Compound, ParseError> {
+        self.pos += 1;
+
+        let word = match self.next() {
+            Some(Token::Word(word)) => word,
+            Some(token) => return Err(ParseError::Unexpected(describe(&token))),
+            None => return Err(ParseError::UnexpectedEof),
+        };
+
+        self.skip_newlines();
+        self.expect_keyword("in")?;
+
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_newlines();
+
+            if is_keyword(self.peek(), "esac") {
+                self.pos += 1;
+                break;
+            }
+
+            if self.peek() == Some(&Token::Operator(Operator::OpenParen)) {
+                self.pos += 1;
+            }
+
+            let mut patterns = Vec::new();
+
+            loop {
+                match self.next() {
+                    Some(Token::Word(pattern)) => patterns.push(pattern),
+                    Some(token) => return Err(ParseError::Unexpected(describe(&token))),
+                    None => return Err(ParseError::UnexpectedEof),
+                }
+
+                match self.next() {
+                    Some(Token::Operator(Operator::Pipe)) => {}
+                    Some(Token::Operator(Operator::CloseParen)) => break,
+                    Some(token) => return Err(ParseError::Unexpected(describe(&token))),
+                    None => return Err(ParseError::UnexpectedEof),
+                }
+            }
+
+            self.skip_newlines();
+
+            let body = if is_keyword(self.peek(), "esac") || case_terminator(self.peek()).is_some()
+            {
+                List::default()
+            } else {
+                self.compound_list(&["esac"])?
+            };
+
+            let terminator = match case_terminator(self.peek()) {
+                Some(terminator) => {
+                    self.pos += 1;
+                    terminator
+                }
+                None if is_keyword(self.peek(), "esac") => CaseTerminator::Break,
+                None => return Err(self.unexpected()),
+            };
+
+            items.push(CaseItem {
+                patterns,
+                body,
+                terminator,
+            });
+        }
+
+        Ok(Compound::Case { word, items })
+    }
+
+    fn do_group(&mut self) -> Result<List, ParseError> {
+        self.skip_newlines();
+        self.expect_keyword("do")?;
+
+        let body = self.compound_list(&["done"])?;
+        self.expect_keyword("done")?;
+
+        Ok(body)
+    }
+
+    fn redirect(&mut self, fd: Option<u32>, op: RedirectOp) -> Result<Redirect, ParseError> {
+        match self.next() {
+            Some(Token::Word(target)) => Ok(Redirect { fd, op, target }),
+            Some(token) => Err(ParseError::Unexpected(describe(&token))),
+            None => Err(ParseError::Unexpected("newline".to_string())),
+        }
+    }
+
+    fn simple_command(&mut self) -> Result<SimpleCommand, ParseError> {
+        let mut command = SimpleCommand::default();
+
+        loop {
+            match self.peek() {
+                Some(Token::Word(_)) => {
+                    if let Some(Token::Word(word)) = self.next() {
+                        match word.as_assignment() {
+                            Some(assignment) if command.words.is_empty() => {
+                                command.assignments.push(assignment)
+                            }
+                            _ => command.words.push(word),
+                        }
+                    }
+                }
+                Some(Token::Redirect { fd, op }) => {
+                    let (fd, op) = (*fd, *op);
+                    self.pos += 1;
+                    command.redirects.push(self.redirect(fd, op)?);
+                }
+                _ => break,
+            }
+        }
+
+        if command.words.is_empty()
+            && command.redirects.is_empty()
+            && command.assignments.is_empty()
+        {
+            return Err(self.unexpected());
+        }
+
+        Ok(command)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::{CaseTerminator, RedirectOp};
+
+    fn simple(command: &Command) -> &SimpleCommand {
+        match command {
+            Command::Simple(command) => command,
+            command => panic!("not a simple command: {:?}", command),
+        }
+    }
+
+    fn words(command: &Command) -> Vec<String> {
+        simple(command)
+            .words
+            .iter()
+            .map(|word| word.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn parses_pipelines_line_by_line() {
+        let list = parse("a 'b c' | d\n\ne").unwrap();
+
+        assert_eq!(list.items.len(), 2);
+        assert_eq!(list.items[0].first.commands.len(), 2);
+        assert_eq!(words(&list.items[0].first.commands[0]), ["a", "\"b c\""]);
+        assert_eq!(words(&list.items[0].first.commands[1]), ["d"]);
+        assert_eq!(words(&list.items[1].first.commands[0]), ["e"]);
+    }
+
+    #[test]
+    fn parses_and_or_lists() {
+        let list = parse("a && b ||\n c; d;").unwrap();
+
+        assert_eq!(list.items.len(), 2);
+
+        let connectors: Vec<Connector> = list.items[0]
+            .rest
+            .iter()
+            .map(|(connector, _)| *connector)
+            .collect();
+
+        assert_eq!(connectors, [Connector::And, Connector::Or]);
+        assert_eq!(words(&list.items[0].rest[1].1.commands[0]), ["c"]);
+        assert!(list.items[1].rest.is_empty());
+    }
+
+    #[test]
+    fn marks_background_lists() {
+        let list = parse("a && b & c; d &").unwrap();
+        let background: Vec<bool> = list.items.iter().map(|item| item.background).collect();
+
+        assert_eq!(background, [true, false, true]);
+        assert_eq!(list.items[0].rest.len(), 1);
+        assert!(matches!(parse("& a"), Err(ParseError::Unexpected(_))));
+    }
+
+    #[test]
+    fn negates_pipelines_with_bang() {
+        let list = parse("! a | b && ! ! c; !d").unwrap();
+        let and_or = &list.items[0];
+
+        assert!(and_or.first.negated);
+        assert_eq!(and_or.first.commands.len(), 2);
+        assert!(!and_or.rest[0].1.negated);
+        assert!(!list.items[1].first.negated);
+        assert_eq!(words(&list.items[1].first.commands[0]), ["!d"]);
+    }
+
+    #[test]
+    fn pipes_stderr_with_bar_ampersand() {
+        let list = parse("a 2>e |& b | c").unwrap();
+        let commands = &list.items[0].first.commands;
+
+        assert_eq!(commands.len(), 3);
+
+        let redirects = &simple(&commands[0]).redirects;
+        assert_eq!(redirects.len(), 2);
+        assert_eq!(redirects[1].fd, Some(2));
+        assert_eq!(redirects[1].op, RedirectOp::DupOutput);
+        assert_eq!(redirects[1].target.to_string(), "1");
+        assert!(simple(&commands[1]).redirects.is_empty());
+    }
+
+    #[test]
+    fn attaches_redirections_to_their_command() {
+        let list = parse("> out echo hi 2>>err").unwrap();
+        let command = &list.items[0].first.commands[0];
+
+        assert_eq!(words(command), ["echo", "hi"]);
+
+        let redirects: Vec<(Option<u32>, RedirectOp, String)> = simple(command)
+            .redirects
+            .iter()
+            .map(|redirect| (redirect.fd, redirect.op, redirect.target.to_string()))
+            .collect();
+
+        assert_eq!(
+            redirects,
+            [
+                (None, RedirectOp::Write, "out".to_string()),
+                (Some(2), RedirectOp::Append, "err".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_arithmetic_commands() {
+        let list = parse("((x += 1)) && echo $((x))").unwrap();
+
+        match &list.items[0].first.commands[0] {
+            Command::Arith(expr) => assert_eq!(expr.expression(), "x += 1"),
+            command => panic!("not an arithmetic command: {:?}", command),
+        }
+
+        assert_eq!(
+            words(&list.items[0].rest[0].1.commands[0]),
+            ["echo", "$((x))"]
+        );
+        assert!(matches!(parse("((x"), Err(ParseError::Incomplete(_))));
+    }
+
+    fn compound(source: &str) -> Compound {
+        let list = parse(source).unwrap();
+
+        match &list.items[0].first.commands[0] {
+            Command::Compound(compound, _) => compound.clone(),
+            command => panic!("not a compound command: {:?}", command),
+        }
+    }
+
+    #[test]
+    fn parses_if_chains() {
+        let Compound::If {
+            branches,
+            otherwise,
+        } = compound("if a; then b; elif c\nthen d; e; else f; fi")
+        else {
+            panic!("not an if command");
+        };
+
+        assert_eq!(branches.len(), 2);
+        assert_eq!(words(&branches[1].0.items[0].first.commands[0]), ["c"]);
+        assert_eq!(branches[1].1.items.len(), 2);
+        assert_eq!(otherwise.unwrap().to_string(), "f;");
+    }
+
+    #[test]
+    fn parses_loops() {
+        assert!(matches!(
+            compound("until a; do b; done"),
+            Compound::While { until: true, .. }
+        ));
+
+        let Compound::For { name, words, body } = compound("for x in a 'b c'; do echo $x; done")
+        else {
+            panic!("not a for loop");
+        };
+
+        assert_eq!(name, "x");
+        assert_eq!(words.unwrap().len(), 2);
+        assert_eq!(body.items.len(), 1);
+
+        assert!(matches!(
+            compound("for x\ndo :; done"),
+            Compound::For { words: None, .. }
+        ));
+        assert!(matches!(
+            compound("for ((i = 0; i < 3; i++)); do :; done"),
+            Compound::ArithFor { .. }
+        ));
+    }
+
+    #[test]
+    fn parses_case_items_and_terminators() {
+        let Compound::Case { word, items } =
+            compound("case $x in\n(a|b) one;;\n c) ;&\n *) two; three ;;&\n d) esac")
+        else {
+            panic!("not a case command");
+        };
+
+        assert_eq!(word.to_string(), "${x}");
+
+        let terminators: Vec<CaseTerminator> = items.iter().map(|item| item.terminator).collect();
+        assert_eq!(
+            terminators,
+            [
+                CaseTerminator::Break,
+                CaseTerminator::FallThrough,
+                CaseTerminator::Continue,
+                CaseTerminator::Break,
+            ]
+        );
+
+        assert_eq!(items[0].patterns.len(), 2);
+        assert!(items[1].body.items.is_empty());
+        assert_eq!(items[2].body.items.len(), 2);
+        assert!(
+            matches!(compound("case a in esac"), Compound::Case { items, .. } if items.is_empty())
+        );
+    }
+
+    #[test]
+    fn parses_function_definitions() {
+        for source in [
+            "f() { a; b; }",
+            "function f { a; b; }",
+            "function f()\n{ a; b; }",
+        ] {
+            let list = parse(source).unwrap();
+
+            let Command::Function(name, body) = &list.items[0].first.commands[0] else {
+                panic!("not a function: {}", source);
+            };
+
+            assert_eq!(name, "f");
+            assert!(matches!(
+                &**body,
+                Command::Compound(Compound::Group(list), _) if list.items.len() == 2
+            ));
+        }
+
+        assert!(matches!(parse("f() echo"), Err(ParseError::Unexpected(_))));
+        assert!(matches!(
+            parse("if() { :; }"),
+            Err(ParseError::Unexpected(_))
+        ));
+    }
+
+    #[test]
+    fn parses_subshells_and_groups_with_redirections() {
+        let list = parse("(a; (b)) >out | { c; } 2>err").unwrap();
+        let commands = &list.items[0].first.commands;
+
+        let Command::Compound(Compound::Subshell(inner), redirects) = &commands[0] else {
+            panic!("not a subshell: {:?}", commands[0]);
+        };
+
+        assert_eq!(inner.items.len(), 2);
+        assert!(matches!(
+            &inner.items[1].first.commands[0],
+            Command::Compound(Compound::Subshell(_), _)
+        ));
+        assert_eq!(redirects[0].target.to_string(), "out");
+        assert!(matches!(
+            &commands[1],
+            Command::Compound(Compound::Group(_), redirects) if redirects[0].fd == Some(2)
+        ));
+
+        assert!(parse("(a").unwrap_err().needs_more_input());
+        assert!(matches!(parse("a)"), Err(ParseError::Unexpected(_))));
+        assert!(matches!(parse("( )"), Err(ParseError::Unexpected(_))));
+    }
+
+    #[test]
+    fn asks_for_more_input_inside_compound_commands() {
+        for source in [
+            "if a; then",
+            "while a\ndo b",
+            "for x in a b",
+            "if a; then b; else",
+        ] {
+            assert!(parse(source).unwrap_err().needs_more_input(), "{}", source);
+        }
+
+        for source in [
+            "if a; fi",
+            "then",
+            "while a; do done",
+            "for 1 in a; do b; done",
+        ] {
+            assert!(!parse(source).unwrap_err().needs_more_input(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn prints_commands_back_as_source() {
+        for source in [
+            "echo \"a b\" \"${x}\" ${y:-z} ${#v} ${p%%*.c}",
+            "f () { a && b; }",
+            "case ${x} in a | b) c; ;; esac",
+        ] {
+            assert_eq!(parse(source).unwrap().to_string(), format!("{};", source));
+        }
+    }
+
+    #[test]
+    fn rejects_misplaced_operators() {
+        assert!(matches!(parse("| a"), Err(ParseError::Unexpected(token)) if token == "|"));
+        assert!(matches!(parse("a > | b"), Err(ParseError::Unexpected(token)) if token == "|"));
+        assert!(matches!(parse("a >"), Err(ParseError::Unexpected(token)) if token == "newline"));
+        assert!(matches!(parse("a |"), Err(ParseError::UnexpectedEof)));
+        assert!(matches!(parse("; a"), Err(ParseError::Unexpected(token)) if token == ";"));
+        assert!(matches!(parse("a && || b"), Err(ParseError::Unexpected(token)) if token == "||"));
+    }
+}
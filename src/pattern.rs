@@ -0,0 +1,346 @@
+#[derive(Debug, Clone, PartialEq)]
+enum ClassItem {
+    Char(char),
+    Range(char, char),
+    Named(String),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Char(char),
+    AnyChar,
+    AnyString,
+    Class {
+        negated: bool,
+        items: Vec<ClassItem>,
+    },
+    Extended {
+        kind: char,
+        alternatives: Vec<Vec<Token>>,
+    },
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    tokens: Vec<Token>,
+}
+
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if matches!(c, '\\' | '*' | '?' | '[' | ']' | '(' | ')' | '|') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+pub fn has_meta(pattern: &str, extglob: bool) -> bool {
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '*' | '?' | '[' => return true,
+            '+' | '@' | '!' if extglob && chars.peek() == Some(&'(') => return true,
+            _ => {}
+        }
+    }
+
+    false
+}
+
+pub fn unescape(pattern: &str) -> String {
+    let mut text = String::new();
+    let mut chars = pattern.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            _ => text.push(c),
+        }
+    }
+
+    text
+}
+
+impl Pattern {
+    pub fn new(pattern: &str, extglob: bool) -> Self {
+        let chars: Vec<char> = pattern.chars().collect();
+
+        Pattern {
+            tokens: parse_tokens(&chars, extglob),
+        }
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        let chars: Vec<char> = text.chars().collect();
+        match_from(&self.tokens, &chars)
+    }
+
+    pub fn matches_hidden(&self) -> bool {
+        self.tokens.first() == Some(&Token::Char('.'))
+    }
+}
+
+fn parse_tokens(chars: &[char], extglob: bool) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < chars.len() {
+        if extglob
+            && matches!(chars[i], '?' | '*' | '+' | '@' | '!')
+            && chars.get(i + 1) == Some(&'(')
+            && let Some((extended, end)) = parse_extended(chars, i + 2, chars[i])
+        {
+            tokens.push(extended);
+            i = end;
+            continue;
+        }
+
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                tokens.push(Token::Char(chars[i + 1]));
+                i += 2;
+            }
+            '*' => {
+                if tokens.last() != Some(&Token::AnyString) {
+                    tokens.push(Token::AnyString);
+                }
+                i += 1;
+            }
+            '?' => {
+                tokens.push(Token::AnyChar);
+                i += 1;
+            }
+            '[' => match parse_class(chars, i + 1) {
+                Some((class, end)) => {
+                    tokens.push(class);
+                    i = end;
+                }
+                None => {
+                    tokens.push(Token::Char('['));
+                    i += 1;
+                }
+            },
+            c => {
+                tokens.push(Token::Char(c));
+                i += 1;
+            }
+        }
+    }
+
+    tokens
+}
+
+fn parse_extended(chars: &[char], start: usize, kind: char) -> Option<(Token, usize)> {
+    let mut alternatives = Vec::new();
+    let mut depth = 0;
+    let mut alternative_start = start;
+    let mut i = start;
+
+    loop {
+        match *chars.get(i)? {
+            '\\' => i += 1,
+            '(' => depth += 1,
+            ')' if depth > 0 => depth -= 1,
+            '|' if depth == 0 => {
+                alternatives.push(parse_tokens(&chars[alternative_start..i], true));
+                alternative_start = i + 1;
+            }
+            ')' => {
+                alternatives.push(parse_tokens(&chars[alternative_start..i], true));
+                return Some((Token::Extended { kind, alternatives }, i + 1));
+            }
+            _ => {}
+        }
+
+        i += 1;
+    }
+}
+
+fn parse_class(chars: &[char], start: usize) -> Option<(Token, usize)> {
+    let mut i = start;
+    let mut negated = false;
+    let mut items = Vec::new();
+
+    if matches!(chars.get(i), Some('!' | '^')) {
+        negated = true;
+        i += 1;
+    }
+
+    let first = i;
+
+    loop {
+        let c = *chars.get(i)?;
+
+        if c == ']' && i > first {
+            return Some((Token::Class { negated, items }, i + 1));
+        }
+
+        if c == '[' && chars.get(i + 1) == Some(&':') {
+            let rest: String = chars[i + 2..].iter().collect();
+
+            if let Some(end) = rest.find(":]") {
+                items.push(ClassItem::Named(rest[..end].to_string()));
+                i += 2 + rest[..end].chars().count() + 2;
+                continue;
+            }
+        }
+
+        let (low, next) = match c {
+            '\\' => (*chars.get(i + 1)?, i + 2),
+            _ => (c, i + 1),
+        };
+
+        if chars.get(next) == Some(&'-') && chars.get(next + 1).is_some_and(|&c| c != ']') {
+            let (high, after) = match chars[next + 1] {
+                '\\' => (*chars.get(next + 2)?, next + 3),
+                high => (high, next + 2),
+            };
+
+            items.push(ClassItem::Range(low, high));
+            i = after;
+        } else {
+            items.push(ClassItem::Char(low));
+            i = next;
+        }
+    }
+}
+
+fn class_matches(negated: bool, items: &[ClassItem], c: char) -> bool {
+    let found = items.iter().any(|item| match item {
+        ClassItem::Char(expected) => *expected == c,
+        ClassItem::Range(low, high) => *low <= c && c <= *high,
+        ClassItem::Named(name) => match name.as_str() {
+            "alpha" => c.is_alphabetic(),
+            "digit" => c.is_ascii_digit(),
+            "alnum" => c.is_alphanumeric(),
+            "upper" => c.is_uppercase(),
+            "lower" => c.is_lowercase(),
+            "space" => c.is_whitespace(),
+            "blank" => c == ' ' || c == '\t',
+            "punct" => c.is_ascii_punctuation(),
+            "xdigit" => c.is_ascii_hexdigit(),
+            "cntrl" => c.is_control(),
+            "print" => !c.is_control(),
+            "graph" => !c.is_control() && !c.is_whitespace(),
+            _ => false,
+        },
+    });
+
+    found != negated
+}
+
+fn match_from(tokens: &[Token], text: &[char]) -> bool {
+    let Some((token, rest)) = tokens.split_first() else {
+        return text.is_empty();
+    };
+
+    match token {
+        Token::AnyString => (0..=text.len()).any(|skip| match_from(rest, &text[skip..])),
+        Token::AnyChar => !text.is_empty() && match_from(rest, &text[1..]),
+        Token::Char(expected) => text.first() == Some(expected) && match_from(rest, &text[1..]),
+        Token::Class { negated, items } => {
+            !text.is_empty()
+                && class_matches(*negated, items, text[0])
+                && match_from(rest, &text[1..])
+        }
+        Token::Extended { kind, alternatives } => (0..=text.len()).any(|end| {
+            extended_matches(*kind, alternatives, &text[..end]) && match_from(rest, &text[end..])
+        }),
+    }
+}
+
+fn extended_matches(kind: char, alternatives: &[Vec<Token>], text: &[char]) -> bool {
+    let any = |text: &[char]| alternatives.iter().any(|tokens| match_from(tokens, text));
+
+    match kind {
+        '?' => text.is_empty() || any(text),
+        '*' => repeat_matches(alternatives, text),
+        '+' if text.is_empty() => any(text),
+        '+' => repeat_matches(alternatives, text),
+        '!' => !any(text),
+        _ => any(text),
+    }
+}
+
+fn repeat_matches(alternatives: &[Vec<Token>], text: &[char]) -> bool {
+    text.is_empty()
+        || (1..=text.len()).any(|end| {
+            alternatives
+                .iter()
+                .any(|tokens| match_from(tokens, &text[..end]))
+                && repeat_matches(alternatives, &text[end..])
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, text: &str) -> bool {
+        Pattern::new(pattern, false).matches(text)
+    }
+
+    #[test]
+    fn wildcards_match_any_text() {
+        assert!(matches("*", ""));
+        assert!(matches("*.rs", "main.rs"));
+        assert!(matches("a*b*c", "aXbYbc"));
+        assert!(!matches("*.rs", "main.c"));
+        assert!(matches("a?c", "abc"));
+        assert!(matches("??", "éa"));
+        assert!(!matches("a?c", "ac"));
+    }
+
+    #[test]
+    fn bracket_expressions_match_one_character() {
+        assert!(matches("[a-c]x", "bx"));
+        assert!(!matches("[a-c]x", "dx"));
+        assert!(matches("[!a]", "b"));
+        assert!(!matches("[^a]", "a"));
+        assert!(matches("[]]", "]"));
+        assert!(matches("[[:digit:]]*", "1st"));
+        assert!(!matches("[[:upper:]]", "a"));
+        assert!(matches("[", "["));
+    }
+
+    #[test]
+    fn escaped_characters_match_literally() {
+        assert!(matches("\\*", "*"));
+        assert!(!matches("\\*", "a"));
+        assert!(matches(&escape("a*[b]"), "a*[b]"));
+        assert!(!matches(&escape("a*"), "abc"));
+    }
+
+    #[test]
+    fn extended_patterns_match_alternatives() {
+        let matches = |pattern: &str, text: &str| Pattern::new(pattern, true).matches(text);
+
+        assert!(matches("@(foo|bar).c", "bar.c"));
+        assert!(!matches("@(foo|bar).c", "foobar.c"));
+        assert!(matches("?(x)y", "y"));
+        assert!(matches("?(x)y", "xy"));
+        assert!(!matches("?(x)y", "xxy"));
+        assert!(matches("*(ab)c", "ababc"));
+        assert!(matches("*(ab)c", "c"));
+        assert!(!matches("+(ab)c", "c"));
+        assert!(matches("+(a|b)", "abba"));
+        assert!(matches("!(*.rs)", "main.c"));
+        assert!(!matches("!(*.rs)", "main.rs"));
+        assert!(!matches("@(a|b)", "@(a|b)"));
+        assert!(matches("@(a|b)", "a"));
+    }
+
+    #[test]
+    fn extended_patterns_are_literal_when_disabled() {
+        assert!(matches("@(a|b)", "@(a|b)"));
+        assert!(!matches("@(a|b)", "a"));
+    }
+}
@@ -1,54 +1,107 @@
-use std::process::{Child, Command, Stdio};
+use std::io::{self, PipeReader};
+use std::os::fd::AsRawFd;
 
-use crate::quoting::parse_args;
+use crate::ast::Pipeline;
+use crate::executor::run_command;
+use crate::jobs::{Job, Jobs};
+use crate::process::{dup2, exit_child, fork, join_process_group};
+use crate::shell::Shell;
+use crate::signals::reset_signals;
 
-pub fn run_pipeline(input: &str) {
-    let commands: Vec<&str> = input.split('|').collect();
+pub fn run_pipeline(shell: &mut Shell, pipeline: &Pipeline) -> Vec<i32> {
+    let commands = &pipeline.commands;
 
-    let mut previous_command_output: Option<Child> = None;
+    let job_control = shell.jobs.job_control;
 
-    for (i, raw_command) in commands.iter().enumerate() {
-        let parts = parse_args(raw_command.trim());
+    let mut previous_reader: Option<PipeReader> = None;
+    let mut children = Vec::new();
+    let mut pgid = 0;
 
-        if parts.is_empty() {
-            continue;
-        }
+    for (i, command) in commands.iter().enumerate() {
+        let stdin = previous_reader.take();
 
-        let stdin = if let Some(mut child) = previous_command_output.take() {
-            Stdio::from(child.stdout.take().expect("Failed to open stdout"))
+        let pipe = if i == commands.len() - 1 {
+            None
         } else {
-            Stdio::inherit()
+            match io::pipe() {
+                Ok(pipe) => Some(pipe),
+                Err(e) => {
+                    eprintln!("{}", e);
+                    children.push(Err(1));
+                    break;
+                }
+            }
         };
 
-        let stdout = if i == commands.len() - 1 {
-            Stdio::inherit()
-        } else {
-            Stdio::piped()
-        };
+        let (reader, writer) = pipe.unzip();
 
-        let child = Command::new(&parts[0])
-            .args(&parts[1..])
-            .stdin(stdin)
-            .stdout(stdout)
-            .spawn();
+        match fork() {
+            Ok(0) => {
+                if job_control {
+                    join_process_group(pgid, true);
+                }
+
+                reset_signals();
+                drop(reader);
+
+                let connected = stdin
+                    .as_ref()
+                    .map_or(Ok(()), |stdin| dup2(stdin.as_raw_fd(), 0))
+                    .and_then(|()| {
+                        writer
+                            .as_ref()
+                            .map_or(Ok(()), |writer| dup2(writer.as_raw_fd(), 1))
+                    });
+
+                if connected.is_err() {
+                    exit_child(1);
+                }
 
-        match child {
-            Ok(child) => {
-                previous_command_output = Some(child);
+                drop(stdin);
+                drop(writer);
+
+                shell.subshell = true;
+                shell.jobs = Jobs::default();
+
+                let status = run_command(shell, command);
+                exit_child(status);
             }
-            Err(_) => match parts[1].as_str() {
-                "exit" | "echo" | "type" | "pwd" | "cd" | "history" => {
-                    println!("{} is a shell builtin", parts[1])
+            Ok(pid) => {
+                if pgid == 0 {
+                    pgid = pid;
                 }
-                _ => {
-                    println!("{}: command not found", parts[0]);
-                    return;
+
+                if job_control {
+                    unsafe { libc::setpgid(pid, pgid) };
                 }
-            },
+
+                children.push(Ok(pid));
+            }
+            Err(e) => {
+                eprintln!("{}", e);
+                children.push(Err(1));
+            }
         }
+
+        previous_reader = reader;
     }
 
-    if let Some(mut final_child) = previous_command_output {
-        final_child.wait().unwrap();
+    let pids: Vec<libc::pid_t> = children.iter().filter_map(|child| child.ok()).collect();
+
+    let mut statuses = if pids.is_empty() {
+        Vec::new()
+    } else {
+        shell
+            .jobs
+            .foreground(Job::new(pgid, &pids, &pipeline.to_string()))
     }
+    .into_iter();
+
+    children
+        .into_iter()
+        .map(|child| match child {
+            Ok(_) => statuses.next().unwrap_or(1),
+            Err(status) => status,
+        })
+        .collect()
 }
@@ -0,0 +1,59 @@
+use std::io::{self, Write};
+
+pub fn fork() -> io::Result<libc::pid_t> {
+    io::stdout().flush()?;
+    io::stderr().flush()?;
+
+    match unsafe { libc::fork() } {
+        -1 => Err(io::Error::last_os_error()),
+        pid => Ok(pid),
+    }
+}
+
+pub fn exit_child(status: i32) -> ! {
+    let _ = io::stdout().flush();
+    let _ = io::stderr().flush();
+
+    unsafe { libc::_exit(status) }
+}
+
+pub fn wait_pid(pid: libc::pid_t) -> i32 {
+    let mut status = 0;
+
+    loop {
+        if unsafe { libc::waitpid(pid, &mut status, 0) } != -1 {
+            return status_code(status);
+        }
+
+        if io::Error::last_os_error().kind() != io::ErrorKind::Interrupted {
+            return 1;
+        }
+    }
+}
+
+pub fn status_code(status: i32) -> i32 {
+    if libc::WIFEXITED(status) {
+        libc::WEXITSTATUS(status)
+    } else if libc::WIFSIGNALED(status) {
+        128 + libc::WTERMSIG(status)
+    } else {
+        1
+    }
+}
+
+pub fn dup2(from: i32, to: i32) -> io::Result<()> {
+    match unsafe { libc::dup2(from, to) } {
+        -1 => Err(io::Error::last_os_error()),
+        _ => Ok(()),
+    }
+}
+
+pub fn join_process_group(pgid: libc::pid_t, foreground: bool) {
+    unsafe {
+        libc::setpgid(0, pgid);
+
+        if foreground {
+            libc::tcsetpgrp(0, libc::getpgrp());
+        }
+    }
+}
@@ -1,58 +1,314 @@
-use std::fs::{File, OpenOptions};
+use std::ffi::CStr;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::fd::{AsRawFd, IntoRawFd, OwnedFd};
 
-pub fn parse_redirection(args: &mut Vec<String>) -> (Option<File>, Option<File>) {
-    let mut stdout_file = None;
-    let mut stderr_file = None;
-    let mut i = 0;
+use crate::ast::{Redirect, RedirectOp};
+use crate::expansion::{ExpansionError, expand_word};
+use crate::process::{dup2, exit_child, fork, wait_pid};
+use crate::shell::Shell;
 
-    while i < args.len() {
-        let arg = &args[i].clone();
+const SAVED_FD_MIN: i32 = 10;
 
-        if arg == ">" || arg == "1>" || arg == "2>" || arg == "2>>" || arg == ">>" || arg == "1>>" {
-            if i + 1 >= args.len() {
-                break;
-            }
+#[derive(Default)]
+pub struct SavedFds {
+    saved: Vec<(i32, Option<i32>)>,
+}
+
+impl SavedFds {
+    fn save(&mut self, fd: i32) {
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
 
-            let filename = &args[i + 1];
+        if self.saved.iter().any(|(saved, _)| *saved == fd) {
+            return;
+        }
 
-            match arg.as_str() {
-                ">" | "1>" => {
-                    let file = File::create(filename).unwrap();
+        let copy = match unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, SAVED_FD_MIN) } {
+            -1 => None,
+            copy => Some(copy),
+        };
 
-                    stdout_file = Some(file)
-                }
-                ">>" | "1>>" => {
-                    let file = OpenOptions::new()
-                        .append(true)
-                        .create(true)
-                        .open(filename)
-                        .expect("Cannot open file.");
-
-                    stdout_file = Some(file)
-                }
-                "2>" => {
-                    let file = File::create(filename).unwrap();
+        self.saved.push((fd, copy));
+    }
+
+    pub fn redirect(&mut self, from: i32, to: i32) -> io::Result<()> {
+        self.save(to);
+        dup2(from, to)
+    }
+
+    fn redirect_file(&mut self, file: File, to: i32) -> io::Result<()> {
+        if file.as_raw_fd() != to {
+            return self.redirect(file.as_raw_fd(), to);
+        }
 
-                    stderr_file = Some(file)
+        let fd = file.into_raw_fd();
+
+        match unsafe { libc::fcntl(fd, libc::F_SETFD, 0) } {
+            -1 => Err(io::Error::last_os_error()),
+            _ => Ok(()),
+        }
+    }
+
+    fn close(&mut self, fd: i32) {
+        self.save(fd);
+        unsafe { libc::close(fd) };
+    }
+
+    pub fn restore(self) {
+        let _ = io::stdout().flush();
+        let _ = io::stderr().flush();
+
+        for (fd, copy) in self.saved.into_iter().rev() {
+            match copy {
+                Some(copy) => {
+                    let _ = dup2(copy, fd);
+                    unsafe { libc::close(copy) };
                 }
-                "2>>" => {
-                    let file = OpenOptions::new()
-                        .append(true)
-                        .create(true)
-                        .open(filename)
-                        .expect("Cannot open file.");
-
-                    stderr_file = Some(file)
+                None => {
+                    unsafe { libc::close(fd) };
                 }
-                _ => todo!(),
             }
+        }
+    }
+}
+
+pub fn apply_redirections(
+    shell: &mut Shell,
+    redirects: &[Redirect],
+    saved: &mut SavedFds,
+) -> Result<(), ExpansionError> {
+    for redirect in redirects {
+        let target = expand_word(shell, &redirect.target)?;
+
+        let op = match redirect.op {
+            RedirectOp::DupOutput
+                if redirect.fd.is_none() && target != "-" && target.parse::<i32>().is_err() =>
+            {
+                RedirectOp::WriteBoth
+            }
+            op => op,
+        };
+
+        let default_fd = match op {
+            RedirectOp::Write
+            | RedirectOp::Clobber
+            | RedirectOp::Append
+            | RedirectOp::DupOutput
+            | RedirectOp::WriteBoth
+            | RedirectOp::AppendBoth => 1,
+            RedirectOp::Read
+            | RedirectOp::ReadWrite
+            | RedirectOp::HereDoc
+            | RedirectOp::HereString
+            | RedirectOp::DupInput => 0,
+        };
+
+        let fd = redirect.fd.map_or(default_fd, |fd| fd as i32);
+
+        let clobber_denied = shell.options.noclobber
+            && matches!(op, RedirectOp::Write | RedirectOp::WriteBoth)
+            && fs::metadata(&target).is_ok_and(|metadata| metadata.is_file());
+
+        if clobber_denied {
+            return Err(ExpansionError::Redirect(
+                target,
+                "cannot overwrite existing file".to_string(),
+            ));
+        }
+
+        let both = matches!(op, RedirectOp::WriteBoth | RedirectOp::AppendBoth);
+
+        if !matches!(op, RedirectOp::DupOutput | RedirectOp::DupInput) {
+            saved.save(fd);
+
+            if both {
+                saved.save(2);
+            }
+        }
+
+        let file = match op {
+            RedirectOp::Write | RedirectOp::Clobber | RedirectOp::WriteBoth => {
+                File::create(&target)
+            }
+            RedirectOp::Append | RedirectOp::AppendBoth => {
+                OpenOptions::new().append(true).create(true).open(&target)
+            }
+            RedirectOp::Read => File::open(&target),
+            RedirectOp::ReadWrite => OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(&target),
+            RedirectOp::HereDoc => here_document(&target),
+            RedirectOp::HereString => here_document(&format!("{}\n", target)),
+            RedirectOp::DupOutput | RedirectOp::DupInput => {
+                duplicate(fd, &target, saved)?;
+                continue;
+            }
+        };
+
+        let file = file.map_err(|e| ExpansionError::Redirect(target, os_message(&e)))?;
 
-            args.remove(i);
-            args.remove(i);
-        } else {
-            i += 1;
+        saved
+            .redirect_file(file, fd)
+            .map_err(|e| ExpansionError::Redirect(fd.to_string(), os_message(&e)))?;
+
+        if both {
+            saved
+                .redirect(fd, 2)
+                .map_err(|e| ExpansionError::Redirect("2".to_string(), os_message(&e)))?;
         }
     }
 
-    (stdout_file, stderr_file)
+    Ok(())
+}
+
+fn duplicate(fd: i32, target: &str, saved: &mut SavedFds) -> Result<(), ExpansionError> {
+    if target == "-" {
+        saved.close(fd);
+        return Ok(());
+    }
+
+    let source = target
+        .parse::<i32>()
+        .ok()
+        .filter(|&source| unsafe { libc::fcntl(source, libc::F_GETFD) } != -1)
+        .ok_or_else(|| ExpansionError::BadFd(target.to_string()))?;
+
+    if source != fd {
+        saved
+            .redirect(source, fd)
+            .map_err(|e| ExpansionError::Redirect(fd.to_string(), os_message(&e)))?;
+    }
+
+    Ok(())
+}
+
+pub fn os_message(error: &io::Error) -> String {
+    match error.raw_os_error() {
+        Some(code) => unsafe { CStr::from_ptr(libc::strerror(code)) }
+            .to_string_lossy()
+            .into_owned(),
+        None => error.to_string(),
+    }
+}
+
+fn here_document(contents: &str) -> io::Result<File> {
+    let (reader, mut writer) = io::pipe()?;
+
+    if contents.len() <= libc::PIPE_BUF {
+        writer.write_all(contents.as_bytes())?;
+        drop(writer);
+
+        return Ok(File::from(OwnedFd::from(reader)));
+    }
+
+    let pid = fork()?;
+
+    if pid == 0 {
+        drop(reader);
+
+        if unsafe { libc::fork() } == 0 {
+            let written = writer.write_all(contents.as_bytes());
+            exit_child(if written.is_ok() { 0 } else { 1 });
+        }
+
+        exit_child(0);
+    }
+
+    drop(writer);
+    wait_pid(pid);
+
+    Ok(File::from(OwnedFd::from(reader)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::Word;
+
+    const OUT: i32 = 57;
+    const ERR: i32 = 58;
+
+    static FDS: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+    fn redirect(fd: i32, op: RedirectOp, target: &str) -> Redirect {
+        let mut word = Word::default();
+        word.push_quoted(target);
+
+        Redirect {
+            fd: Some(fd as u32),
+            op,
+            target: word,
+        }
+    }
+
+    fn run(name: &str, redirects: impl Fn(&str) -> Vec<Redirect>) -> (String, String) {
+        let _lock = FDS.lock().unwrap();
+        let dir = std::env::temp_dir().join(format!("redirect-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let terminal = dir.join("terminal");
+        let file = dir.join("file");
+        let _ = fs::remove_file(&file);
+
+        let mut shell = Shell::default();
+        let mut outer = SavedFds::default();
+        let opened = File::create(&terminal).unwrap();
+        outer.redirect(opened.as_raw_fd(), OUT).unwrap();
+        outer.redirect(opened.as_raw_fd(), ERR).unwrap();
+
+        let mut saved = SavedFds::default();
+        apply_redirections(&mut shell, &redirects(file.to_str().unwrap()), &mut saved).unwrap();
+        assert_eq!(unsafe { libc::write(ERR, b"err\n".as_ptr().cast(), 4) }, 4);
+        saved.restore();
+        outer.restore();
+
+        let contents = (
+            fs::read_to_string(&terminal).unwrap(),
+            fs::read_to_string(&file).unwrap(),
+        );
+
+        fs::remove_dir_all(dir).unwrap();
+        contents
+    }
+
+    #[test]
+    fn duplicates_the_target_at_the_time_of_the_redirection() {
+        let (terminal, file) = run("before", |file| {
+            vec![
+                redirect(ERR, RedirectOp::DupOutput, &OUT.to_string()),
+                redirect(OUT, RedirectOp::Write, file),
+            ]
+        });
+
+        assert_eq!((terminal.as_str(), file.as_str()), ("err\n", ""));
+    }
+
+    #[test]
+    fn follows_earlier_redirections() {
+        let (terminal, file) = run("after", |file| {
+            vec![
+                redirect(OUT, RedirectOp::Write, file),
+                redirect(ERR, RedirectOp::DupOutput, &OUT.to_string()),
+            ]
+        });
+
+        assert_eq!((terminal.as_str(), file.as_str()), ("", "err\n"));
+    }
+
+    #[test]
+    fn rejects_closed_descriptors() {
+        let mut shell = Shell::default();
+        let mut saved = SavedFds::default();
+        let redirects = [redirect(ERR, RedirectOp::DupOutput, "59")];
+
+        assert!(matches!(
+            apply_redirections(&mut shell, &redirects, &mut saved),
+            Err(ExpansionError::BadFd(fd)) if fd == "59"
+        ));
+        saved.restore();
+    }
 }
@@ -0,0 +1,79 @@
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
+
+use crate::executor::run_list;
+use crate::parser::parse;
+use crate::redirection::os_message;
+use crate::shell::{Flow, Shell};
+
+pub fn run_file(shell: &mut Shell, path: &str, args: &[String]) -> i32 {
+    let file = match File::open(path) {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("{}: {}: {}", shell.arg0, path, os_message(&e));
+            return 127;
+        }
+    };
+
+    shell.arg0 = path.to_string();
+    shell.positional = args.to_vec();
+
+    run_source(shell, BufReader::new(file))
+}
+
+struct StdinFd;
+
+impl Read for StdinFd {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match unsafe { libc::read(0, buf.as_mut_ptr().cast(), buf.len()) } {
+                -1 if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted => {}
+                -1 => return Err(io::Error::last_os_error()),
+                n => return Ok(n as usize),
+            }
+        }
+    }
+}
+
+pub fn stdin_source() -> impl BufRead {
+    BufReader::with_capacity(1, StdinFd)
+}
+
+pub fn run_source(shell: &mut Shell, source: impl BufRead) -> i32 {
+    let mut pending = String::new();
+
+    for line in source.lines() {
+        let Ok(line) = line else {
+            break;
+        };
+
+        if !pending.is_empty() {
+            pending.push('\n');
+        }
+        pending.push_str(&line);
+
+        match parse(&pending) {
+            Ok(list) => {
+                run_list(shell, &list);
+
+                if shell.flow == Some(Flow::Abort) {
+                    shell.flow = None;
+                }
+            }
+            Err(e) if e.needs_more_input() => continue,
+            Err(e) => {
+                eprintln!("{}: {}", shell.arg0, e);
+                return 2;
+            }
+        }
+
+        pending.clear();
+    }
+
+    if let Err(e) = parse(&pending) {
+        eprintln!("{}: {}", shell.arg0, e);
+        return 2;
+    }
+
+    shell.last_status
+}
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::rc::Rc;
+
+use crate::ast::Command;
+
+use crate::jobs::Jobs;
+use crate::options::Options;
+use crate::redirection::os_message;
+use crate::variables::Variables;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Flow {
+    Break(usize),
+    Continue(usize),
+    Return(i32),
+    Abort,
+}
+
+#[derive(Default)]
+pub struct Shell {
+    pub history: Vec<String>,
+    pub history_start: usize,
+    pub last_status: i32,
+    pub pipestatus: Vec<i32>,
+    pub vars: Variables,
+    pub substitution_status: Option<i32>,
+    pub options: Options,
+    pub interactive: bool,
+    pub subshell: bool,
+    pub jobs: Jobs,
+    pub last_background: Option<libc::pid_t>,
+    pub flow: Option<Flow>,
+    pub loop_depth: usize,
+    pub functions: HashMap<String, Rc<Command>>,
+    pub function_depth: usize,
+    pub arg0: String,
+    pub positional: Vec<String>,
+}
+
+impl Shell {
+    pub fn new() -> Self {
+        Shell {
+            history: Vec::new(),
+            history_start: 0,
+            last_status: 0,
+            pipestatus: vec![0],
+            vars: Variables::from_env(),
+            substitution_status: None,
+            options: Options::default(),
+            interactive: false,
+            subshell: false,
+            jobs: Jobs::default(),
+            last_background: None,
+            flow: None,
+            loop_depth: 0,
+            functions: HashMap::new(),
+            function_depth: 0,
+            arg0: env::args().next().unwrap_or_default(),
+            positional: Vec::new(),
+        }
+    }
+
+    pub fn load_history(&mut self) {
+        let Ok(path) = env::var("HISTFILE") else {
+            return;
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => self.history.extend(contents.lines().map(String::from)),
+            Err(e) => eprintln!("{}: {}: {}", self.arg0, path, os_message(&e)),
+        }
+
+        self.history_start = self.history.len();
+    }
+
+    pub fn save_history(&self) {
+        let Ok(path) = env::var("HISTFILE") else {
+            return;
+        };
+
+        let result = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .and_then(|mut file| {
+                for cmd in &self.history[self.history_start..] {
+                    writeln!(file, "{}", cmd)?;
+                }
+
+                Ok(())
+            });
+
+        if let Err(e) = result {
+            eprintln!("{}: {}: {}", self.arg0, path, os_message(&e));
+        }
+    }
+}
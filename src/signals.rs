@@ -0,0 +1,104 @@
+use std::ffi::CStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+pub static CHILD_CHANGED: AtomicBool = AtomicBool::new(false);
+
+const IGNORED_SIGNALS: &[i32] = &[
+    libc::SIGINT,
+    libc::SIGQUIT,
+    libc::SIGTERM,
+    libc::SIGTSTP,
+    libc::SIGTTIN,
+    libc::SIGTTOU,
+];
+
+pub const SIGNALS: &[(&str, i32)] = &[
+    ("HUP", libc::SIGHUP),
+    ("INT", libc::SIGINT),
+    ("QUIT", libc::SIGQUIT),
+    ("ILL", libc::SIGILL),
+    ("TRAP", libc::SIGTRAP),
+    ("ABRT", libc::SIGABRT),
+    ("BUS", libc::SIGBUS),
+    ("FPE", libc::SIGFPE),
+    ("KILL", libc::SIGKILL),
+    ("USR1", libc::SIGUSR1),
+    ("SEGV", libc::SIGSEGV),
+    ("USR2", libc::SIGUSR2),
+    ("PIPE", libc::SIGPIPE),
+    ("ALRM", libc::SIGALRM),
+    ("TERM", libc::SIGTERM),
+    ("CHLD", libc::SIGCHLD),
+    ("CONT", libc::SIGCONT),
+    ("STOP", libc::SIGSTOP),
+    ("TSTP", libc::SIGTSTP),
+    ("TTIN", libc::SIGTTIN),
+    ("TTOU", libc::SIGTTOU),
+    ("URG", libc::SIGURG),
+    ("XCPU", libc::SIGXCPU),
+    ("XFSZ", libc::SIGXFSZ),
+    ("VTALRM", libc::SIGVTALRM),
+    ("PROF", libc::SIGPROF),
+    ("WINCH", libc::SIGWINCH),
+    ("IO", libc::SIGIO),
+    ("SYS", libc::SIGSYS),
+];
+
+pub fn signal_number(name: &str) -> Option<i32> {
+    if let Ok(number) = name.parse::<i32>() {
+        return Some(number);
+    }
+
+    let name = name.to_ascii_uppercase();
+    let name = name.strip_prefix("SIG").unwrap_or(&name);
+
+    SIGNALS
+        .iter()
+        .find(|(signal, _)| *signal == name)
+        .map(|(_, number)| *number)
+}
+
+pub fn describe_signal(signal: i32, core_dumped: bool) -> String {
+    let description = unsafe { CStr::from_ptr(libc::strsignal(signal)) }.to_string_lossy();
+
+    if core_dumped {
+        format!("{} (core dumped)", description)
+    } else {
+        description.into_owned()
+    }
+}
+
+extern "C" fn child_changed(_: libc::c_int) {
+    CHILD_CHANGED.store(true, Ordering::SeqCst);
+}
+
+fn set_handler(signal: i32, handler: libc::sighandler_t) {
+    unsafe {
+        let mut action: libc::sigaction = std::mem::zeroed();
+        action.sa_sigaction = handler;
+        action.sa_flags = libc::SA_RESTART;
+        libc::sigemptyset(&mut action.sa_mask);
+        libc::sigaction(signal, &action, std::ptr::null_mut());
+    }
+}
+
+pub fn init_signals(interactive: bool) {
+    set_handler(
+        libc::SIGCHLD,
+        child_changed as *const () as libc::sighandler_t,
+    );
+
+    if !interactive {
+        return;
+    }
+
+    for &signal in IGNORED_SIGNALS {
+        set_handler(signal, libc::SIG_IGN);
+    }
+}
+
+pub fn reset_signals() {
+    for &signal in IGNORED_SIGNALS.iter().chain(&[libc::SIGPIPE]) {
+        set_handler(signal, libc::SIG_DFL);
+    }
+}
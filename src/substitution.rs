@@ -0,0 +1,48 @@
+use std::io::{self, Read};
+use std::os::fd::AsRawFd;
+
+use crate::executor::run_list;
+use crate::expansion::ExpansionError;
+use crate::jobs::Jobs;
+use crate::parser::parse;
+use crate::process::{dup2, exit_child, fork, wait_pid};
+use crate::shell::Shell;
+use crate::signals::reset_signals;
+
+pub fn command_substitution(shell: &mut Shell, body: &str) -> Result<String, ExpansionError> {
+    let list = parse(body)?;
+    let (mut reader, writer) = io::pipe()?;
+
+    let pid = fork()?;
+
+    if pid == 0 {
+        drop(reader);
+
+        if dup2(writer.as_raw_fd(), 1).is_err() {
+            exit_child(1);
+        }
+        drop(writer);
+
+        shell.subshell = true;
+        shell.jobs = Jobs::default();
+        reset_signals();
+
+        let status = run_list(shell, &list);
+        exit_child(status);
+    }
+
+    drop(writer);
+
+    let mut output = Vec::new();
+    let _ = reader.read_to_end(&mut output);
+
+    let status = wait_pid(pid);
+    shell.last_status = status;
+    shell.substitution_status = Some(status);
+
+    let mut output = String::from_utf8_lossy(&output).into_owned();
+    let trimmed_len = output.trim_end_matches('\n').len();
+    output.truncate(trimmed_len);
+
+    Ok(output)
+}
@@ -0,0 +1,86 @@
+use std::env;
+use std::ffi::{CStr, CString};
+
+use crate::ast::{Word, WordPart};
+use crate::shell::Shell;
+
+pub fn tilde_prefix(shell: &Shell, word: &Word) -> Option<(String, String)> {
+    let Some(WordPart::Literal(text)) = word.parts.first() else {
+        return None;
+    };
+
+    let rest = text.strip_prefix('~')?;
+
+    let (user, path) = match rest.find('/') {
+        Some(i) => rest.split_at(i),
+        None if word.parts.len() == 1 => (rest, ""),
+        None => return None,
+    };
+
+    Some((expand_tilde(shell, user)?, path.to_string()))
+}
+
+fn expand_tilde(shell: &Shell, user: &str) -> Option<String> {
+    match user {
+        "" => shell
+            .vars
+            .get("HOME")
+            .map(String::from)
+            .or_else(|| Some(env::home_dir()?.display().to_string())),
+        "+" => shell
+            .vars
+            .get("PWD")
+            .map(String::from)
+            .or_else(|| Some(env::current_dir().ok()?.display().to_string())),
+        "-" => shell.vars.get("OLDPWD").map(String::from),
+        user => home_of(user),
+    }
+}
+
+fn home_of(user: &str) -> Option<String> {
+    let name = CString::new(user).ok()?;
+    let entry = unsafe { libc::getpwnam(name.as_ptr()) };
+
+    if entry.is_null() {
+        return None;
+    }
+
+    let dir = unsafe { CStr::from_ptr((*entry).pw_dir) };
+    Some(dir.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prefix(shell: &Shell, parts: Vec<WordPart>) -> Option<(String, String)> {
+        tilde_prefix(shell, &Word { parts })
+    }
+
+    #[test]
+    fn expands_home_and_directory_stack_prefixes() {
+        let mut shell = Shell::default();
+        shell.vars.set("HOME", "/home/me");
+        shell.vars.set("OLDPWD", "/old");
+
+        let literal = |text: &str| WordPart::Literal(text.to_string());
+
+        assert_eq!(
+            prefix(&shell, vec![literal("~/src")]),
+            Some(("/home/me".to_string(), "/src".to_string()))
+        );
+        assert_eq!(
+            prefix(&shell, vec![literal("~-")]),
+            Some(("/old".to_string(), String::new()))
+        );
+        assert_eq!(prefix(&shell, vec![literal("a~")]), None);
+        assert_eq!(
+            prefix(
+                &shell,
+                vec![literal("~"), WordPart::Quoted("x".to_string())]
+            ),
+            None
+        );
+        assert_eq!(prefix(&shell, vec![literal("~no-such-user-here/x")]), None);
+    }
+}
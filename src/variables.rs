@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::env;
+
+#[derive(Clone)]
+pub struct Variable {
+    pub value: Option<String>,
+    pub exported: bool,
+}
+
+#[derive(Default)]
+pub struct Variables {
+    vars: HashMap<String, Variable>,
+    scopes: Vec<Vec<(String, Option<Variable>)>>,
+}
+
+impl Variables {
+    pub fn from_env() -> Self {
+        let vars = env::vars()
+            .map(|(name, value)| {
+                (
+                    name,
+                    Variable {
+                        value: Some(value),
+                        exported: true,
+                    },
+                )
+            })
+            .collect();
+
+        Variables {
+            vars,
+            scopes: Vec::new(),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.vars.get(name).and_then(|var| var.value.as_deref())
+    }
+
+    pub fn set(&mut self, name: &str, value: &str) {
+        let var = self.vars.entry(name.to_string()).or_insert(Variable {
+            value: None,
+            exported: false,
+        });
+
+        var.value = Some(value.to_string());
+    }
+
+    pub fn export(&mut self, name: &str, value: Option<&str>) {
+        let var = self.vars.entry(name.to_string()).or_insert(Variable {
+            value: None,
+            exported: true,
+        });
+
+        var.exported = true;
+
+        if let Some(value) = value {
+            var.value = Some(value.to_string());
+        }
+    }
+
+    pub fn unset(&mut self, name: &str) {
+        self.vars.remove(name);
+    }
+
+    pub fn snapshot(&self, name: &str) -> Option<Variable> {
+        self.vars.get(name).cloned()
+    }
+
+    pub fn restore(&mut self, name: &str, snapshot: Option<Variable>) {
+        match snapshot {
+            Some(var) => {
+                self.vars.insert(name.to_string(), var);
+            }
+            None => self.unset(name),
+        }
+    }
+
+    pub fn push_scope(&mut self) {
+        self.scopes.push(Vec::new());
+    }
+
+    pub fn pop_scope(&mut self) {
+        let Some(scope) = self.scopes.pop() else {
+            return;
+        };
+
+        for (name, snapshot) in scope.into_iter().rev() {
+            self.restore(&name, snapshot);
+        }
+    }
+
+    pub fn local(&mut self, name: &str, value: Option<&str>) {
+        let snapshot = self.snapshot(name);
+
+        let Some(scope) = self.scopes.last_mut() else {
+            return;
+        };
+
+        if !scope.iter().any(|(existing, _)| existing == name) {
+            scope.push((name.to_string(), snapshot));
+        }
+
+        match value {
+            Some(value) => self.set(name, value),
+            None => {
+                self.vars.insert(
+                    name.to_string(),
+                    Variable {
+                        value: None,
+                        exported: false,
+                    },
+                );
+            }
+        }
+    }
+
+    pub fn exported(&self) -> Vec<(String, String)> {
+        let mut exported: Vec<(String, String)> = self
+            .vars
+            .iter()
+            .filter(|(_, var)| var.exported)
+            .filter_map(|(name, var)| Some((name.clone(), var.value.clone()?)))
+            .collect();
+
+        exported.sort();
+        exported
+    }
+
+    pub fn exported_names(&self) -> Vec<(String, Option<String>)> {
+        let mut exported: Vec<(String, Option<String>)> = self
+            .vars
+            .iter()
+            .filter(|(_, var)| var.exported)
+            .map(|(name, var)| (name.clone(), var.value.clone()))
+            .collect();
+
+        exported.sort();
+        exported
+    }
+}
+
+pub fn is_valid_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn locals_are_restored_when_the_scope_ends() {
+        let mut vars = Variables::default();
+        vars.export("x", Some("global"));
+
+        vars.push_scope();
+        vars.local("x", Some("outer"));
+        vars.local("y", None);
+        assert_eq!(vars.get("x"), Some("outer"));
+        assert_eq!(vars.exported(), [("x".to_string(), "outer".to_string())]);
+
+        vars.push_scope();
+        vars.local("x", Some("inner"));
+        vars.local("x", Some("again"));
+        vars.set("y", "set");
+        vars.pop_scope();
+
+        assert_eq!((vars.get("x"), vars.get("y")), (Some("outer"), Some("set")));
+
+        vars.pop_scope();
+        assert_eq!(vars.get("y"), None);
+        assert_eq!(vars.exported(), [("x".to_string(), "global".to_string())]);
+    }
+
+    #[test]
+    fn local_outside_a_function_does_nothing() {
+        let mut vars = Variables::default();
+        vars.local("x", Some("1"));
+        assert_eq!(vars.get("x"), None);
+        assert!(!is_valid_name("1x"));
+        assert!(is_valid_name("_x1"));
+    }
+}
@@ -0,0 +1,120 @@
+use std::env;
+use std::fs;
+use std::os::unix::fs::PermissionsExt;
+use std::process::Command;
+
+fn run(script: &str) -> (String, String, i32) {
+    let output = Command::new(env!("CARGO_BIN_EXE_codecrafters-shell"))
+        .args(["-c", script, "sh"])
+        .env_remove("HISTFILE")
+        .output()
+        .unwrap();
+
+    (
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+        output.status.code().unwrap_or(-1),
+    )
+}
+
+#[test]
+fn assigns_left_to_right() {
+    let (stdout, _, _) = run("x=5 y=$x; echo $y");
+    assert_eq!(stdout, "5\n");
+
+    let (stdout, _, _) = run("a=1 b=$a env | grep ^b=; echo \"[$a$b]\"");
+    assert_eq!(stdout, "b=1\n[]\n");
+
+    let (stdout, _, _) = run("x=1; x=2 echo $x");
+    assert_eq!(stdout, "1\n");
+}
+
+#[test]
+fn exports_prefix_assignments_for_functions_and_builtins() {
+    let (stdout, _, _) = run("f(){ env | grep ^Q=; }; Q=9 f; Q=1; Q=2 f; f; echo $Q");
+    assert_eq!(stdout, "Q=9\nQ=2\n1\n");
+
+    let (stdout, _, _) = run("S=3 export -p | grep ' S='; export -p | grep -c ' S='");
+    assert_eq!(stdout, "declare -x S=\"3\"\n0\n");
+}
+
+#[test]
+fn reports_why_a_command_could_not_run() {
+    let (_, stderr, status) = run("no-such-command-here");
+    assert_eq!(
+        (stderr.as_str(), status),
+        ("no-such-command-here: command not found\n", 127)
+    );
+
+    let (_, stderr, status) = run("/tmp");
+    assert!(stderr.ends_with(": /tmp: Is a directory\n"), "{}", stderr);
+    assert_eq!(status, 126);
+
+    let path = env::temp_dir().join(format!("commands-plain-{}", std::process::id()));
+    fs::write(&path, "echo hi\n").unwrap();
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let (_, stderr, status) = run(path.to_str().unwrap());
+    fs::remove_file(&path).unwrap();
+
+    assert!(stderr.ends_with(": Permission denied\n"), "{}", stderr);
+    assert_eq!(status, 126);
+
+    let (_, stderr, status) = run("/nonexistent/command");
+    assert!(stderr.ends_with(": /nonexistent/command: No such file or directory\n"));
+    assert_eq!(status, 127);
+}
+
+#[test]
+fn looks_commands_up_in_the_shell_path() {
+    let (stdout, stderr, status) = run("unset PATH; ls /");
+    assert_eq!(
+        (stdout.as_str(), stderr.as_str()),
+        ("", "ls: command not found\n")
+    );
+    assert_eq!(status, 127);
+
+    let (stdout, _, _) = run("PATH=/nonexistent:/bin:/usr/bin; sh -c 'echo $0'");
+    assert_eq!(stdout, "sh\n");
+
+    let (_, stderr, status) = run("PATH=/nonexistent env true");
+    assert_eq!((stderr.as_str(), status), ("env: command not found\n", 127));
+}
+
+#[test]
+fn wait_reports_stopped_jobs_instead_of_blocking() {
+    let (stdout, _, _) = run(
+        "sleep 1 & kill -STOP %1; wait %1; echo $?; jobs; kill -CONT %1; kill %1; wait %1; echo $?",
+    );
+
+    assert_eq!(
+        stdout,
+        format!(
+            "{0}\n147\n{0}\n143\n",
+            "[1]+  Stopped                 sleep 1"
+        )
+    );
+}
+
+#[test]
+fn failglob_abandons_the_rest_of_the_line() {
+    let (stdout, stderr, _) = run(
+        "shopt -s failglob\necho /nonexistent/*.zz; echo after $?\nf() { echo /nonexistent/*; echo in f; }; f; echo after f\necho next $?",
+    );
+
+    assert_eq!(stdout, "next 1\n");
+    assert_eq!(stderr.matches("no match: /nonexistent/").count(), 2);
+}
+
+#[test]
+fn prefixes_redirection_errors_with_the_shell_name() {
+    let (stdout, stderr, status) =
+        run("echo hi > /nonexistent/file; { echo group; } < /nonexistent/in; echo $?");
+
+    assert_eq!(stdout, "1\n");
+    assert_eq!(
+        stderr,
+        "sh: /nonexistent/file: No such file or directory\nsh: /nonexistent/in: No such file or directory\n"
+    );
+    assert_eq!(status, 0);
+}
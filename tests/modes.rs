@@ -0,0 +1,111 @@
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn shell(args: &[&str], stdin: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_codecrafters-shell"))
+        .args(args)
+        .env_remove("HISTFILE")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+fn stderr(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stderr).into_owned()
+}
+
+#[test]
+fn runs_command_strings_with_positional_parameters() {
+    let output = shell(&["-c", "echo $0 $# \"$@\"; exit 3", "name", "a b", "c"], "");
+
+    assert_eq!(stdout(&output), "name 2 a b c\n");
+    assert_eq!(output.status.code(), Some(3));
+}
+
+#[test]
+fn command_strings_return_the_last_status() {
+    let output = shell(&["-c", "false || true; (exit 4)"], "");
+    assert_eq!(output.status.code(), Some(4));
+
+    let output = shell(&["-c"], "");
+    assert!(stderr(&output).ends_with("-c: option requires an argument\n"));
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn reads_commands_from_stdin() {
+    let script = "x=1\nif [ $x = 1 ]\nthen\n  echo yes\nfi\nfor i in \"$@\"; do echo $i; done\n";
+
+    let output = shell(&["-s", "a", "b"], script);
+    assert_eq!(stdout(&output), "yes\na\nb\n");
+
+    let output = shell(&[], "echo piped\n");
+    assert_eq!(stdout(&output), "piped\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn runs_script_files_with_arguments() {
+    let path = env::temp_dir().join(format!("modes-script-{}.sh", std::process::id()));
+    fs::write(
+        &path,
+        "f() {\n  echo \"$1:$#\"\n}\nf \"$2\" x\necho $0 | grep -c modes-script\n",
+    )
+    .unwrap();
+
+    let output = shell(&[path.to_str().unwrap(), "one", "two"], "");
+    fs::remove_file(&path).unwrap();
+
+    assert_eq!(stdout(&output), "two:2\n1\n");
+    assert_eq!(output.status.code(), Some(0));
+}
+
+#[test]
+fn stops_at_syntax_errors_and_missing_scripts() {
+    let output = shell(&["-c", "echo before\nfi\necho after"], "");
+
+    assert_eq!(stdout(&output), "before\n");
+    assert!(stderr(&output).contains("syntax error"));
+    assert_eq!(output.status.code(), Some(2));
+
+    let output = shell(&["/nonexistent/script.sh"], "");
+    assert!(stderr(&output).contains("/nonexistent/script.sh: No such file or directory"));
+    assert_eq!(output.status.code(), Some(127));
+}
+
+#[test]
+fn leaves_history_alone_outside_the_prompt() {
+    let path = env::temp_dir().join(format!("modes-history-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    for histfile in [path.to_str().unwrap(), "/nonexistent/history"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_codecrafters-shell"))
+            .args(["-c", "echo hi; exit 5"])
+            .env("HISTFILE", histfile)
+            .output()
+            .unwrap();
+
+        assert_eq!(stdout(&output), "hi\n");
+        assert_eq!(stderr(&output), "");
+        assert_eq!(output.status.code(), Some(5));
+    }
+
+    assert!(!path.exists());
+}